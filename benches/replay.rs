@@ -0,0 +1,46 @@
+//! # Replaying logs
+//!
+//! Applies the ops of logs of increasing length to an empty chronofold, e.g.
+//! when loading a document or syncing a new replica. The time per op should
+//! stay constant.
+
+use chronofold::{Chronofold, LogIndex, Op};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+fn replay(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Replay");
+    group.sample_size(10);
+    for n in [1_000, 10_000, 100_000] {
+        let ops = edit_ops(n);
+        group.throughput(Throughput::Elements(ops.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &ops, |b, ops| {
+            b.iter(|| {
+                let mut cfold = Chronofold::<u8, char>::empty();
+                for op in ops.iter().cloned() {
+                    cfold.apply(op).unwrap();
+                }
+                cfold
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Returns the ops of typing, with every tenth op deleting the second to
+/// last character.
+fn edit_ops(n: usize) -> Vec<Op<u8, char>> {
+    let mut cfold = Chronofold::<u8, char>::default();
+    let mut session = cfold.session(1);
+    let mut last = LogIndex(0);
+    for i in 0..n {
+        if i % 10 == 9 {
+            session.remove(LogIndex(last.0 - 1));
+        } else {
+            last = session.insert_after(last, (b'a' + (i % 26) as u8) as char);
+        }
+    }
+    cfold.iter_ops(..).map(Op::cloned).collect()
+}
+
+criterion_group!(benches, replay);
+criterion_main!(benches);
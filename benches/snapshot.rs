@@ -0,0 +1,30 @@
+//! # Writing after a snapshot
+//!
+//! Takes a snapshot of chronofolds of increasing length and makes a single
+//! edit, which has to copy the shared parts of the log it modifies. The time
+//! should grow logarithmically with the length.
+
+use chronofold::{Chronofold, LogIndex, SnapshotStorage};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn write_after_snapshot(c: &mut Criterion) {
+    let mut group = c.benchmark_group("WriteAfterSnapshot");
+    for n in [1_000, 10_000, 100_000] {
+        let mut cfold = Chronofold::<u8, char, _>::new_in(0, SnapshotStorage::new());
+        cfold.session(1).extend("a".repeat(n).chars());
+        group.bench_with_input(BenchmarkId::from_parameter(n), &cfold, |b, cfold| {
+            b.iter_batched(
+                || (cfold.clone(), cfold.snapshot_ref()),
+                |(mut cfold, snapshot)| {
+                    cfold.session(2).insert_after(LogIndex(n / 2), 'b');
+                    (cfold, snapshot)
+                },
+                criterion::BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, write_after_snapshot);
+criterion_main!(benches);
@@ -0,0 +1,7 @@
+#![no_main]
+
+use chronofold::Op;
+use chronofold_fuzz::apply_ops;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|ops: Vec<Op<u8, char>>| apply_ops(ops));
@@ -0,0 +1,19 @@
+#![no_main]
+
+use chronofold::{Chronofold, Version};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Deserializing checks the invariants, so everything else must not
+    // panic.
+    if let Ok(mut cfold) = serde_json::from_slice::<Chronofold<u8, char>>(data) {
+        let _ = format!("{cfold}");
+        let _ = cfold.iter_ops::<_, &char>(..).count();
+        let _ = cfold.iter_newer_ops::<&char>(&Version::new()).count();
+        let mut session = cfold.session(1);
+        // Chronofolds without a root can't be edited before creating one.
+        let _ = session.create_root();
+        session.extend("fuzz".chars());
+        assert_eq!(Ok(()), cfold.check_invariants());
+    }
+});
@@ -0,0 +1,72 @@
+//! Code shared by the fuzz targets.
+
+use chronofold::testing::check_ops_against_reference;
+use chronofold::{Chronofold, LogIndex, Op, OpPayload, Timestamp};
+
+/// Maps an op onto `known` timestamps, so a chronofold containing them is
+/// likely to accept it.
+///
+/// Arbitrary timestamps are almost always unknown or too large to be
+/// accepted, so this maps references to known timestamps and reduces the
+/// id's log index and author.
+pub fn reduce_op<T>(op: Op<u8, T>, known: &[Timestamp<u8>]) -> Op<u8, T> {
+    let reference = |r: Timestamp<u8>| known[r.0 .0 % known.len()];
+    let payload = match op.payload {
+        OpPayload::Root => OpPayload::Root,
+        OpPayload::Insert(r, v) => OpPayload::Insert(r.map(reference), v),
+        OpPayload::Delete(r) => OpPayload::Delete(reference(r)),
+    };
+    let id = Timestamp(LogIndex(op.id.0 .0 % (known.len() + 1)), op.id.1 % 3);
+    Op::new(id, payload)
+}
+
+/// Applies each op as it is and mapped onto known timestamps, checking the
+/// invariants after every successful apply.
+///
+/// Applying the resulting ops in reverse order must converge.
+pub fn apply_ops(ops: Vec<Op<u8, char>>) {
+    let mut cfold = Chronofold::<u8, char>::default();
+    let mut known = vec![Timestamp(LogIndex(0), 0)];
+    for op in ops {
+        let reduced = reduce_op(op.clone(), &known);
+        for op in [op, reduced] {
+            let id = op.id;
+            if cfold.apply(op).is_ok() {
+                assert_eq!(Ok(()), cfold.check_invariants());
+                known.push(id);
+            }
+        }
+    }
+    let _ = format!("{cfold}");
+
+    let ops: Vec<Op<u8, char>> = cfold.iter_ops(..).map(Op::cloned).collect();
+    let reversed = check_ops_against_reference(ops.into_iter().rev()).unwrap();
+    assert!(reversed.content_eq(&cfold));
+}
+
+#[cfg(test)]
+mod tests {
+    use chronofold::testing::Rng;
+    use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
+
+    use super::*;
+
+    /// Runs `apply_ops` on random inputs, as a quick check without a fuzzer.
+    #[test]
+    fn apply_random_ops() {
+        let mut rng = Rng::seed_from(0);
+        for _ in 0..1000 {
+            let len = rng.gen_range(0..512);
+            let bytes: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+            let mut u = Unstructured::new(&bytes);
+            let mut ops = vec![];
+            while let Ok(op) = Op::<u8, char>::arbitrary(&mut u) {
+                if u.is_empty() {
+                    break;
+                }
+                ops.push(op);
+            }
+            apply_ops(ops);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Zero-copy archives of chronofolds.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use rkyv::api::high::{HighSerializer, HighValidator};
+use rkyv::bytecheck::CheckBytes;
+use rkyv::de::Pool;
+use rkyv::rancor::{Error, Strategy};
+use rkyv::ser::allocator::ArenaHandle;
+use rkyv::util::AlignedVec;
+use rkyv::with::{ArchiveWith, Inline};
+use rkyv::{Archive, Archived, Deserialize, Serialize};
+
+use crate::index::IndexShift;
+use crate::{
+    ArchivedChange, ArchivedLogIndex, Author, Change, Chronofold, EarliestDeletion, InvariantError,
+    LogIndex, MemoryStorage, Timestamp, Version,
+};
+
+/// The fields of a chronofold, with secondary logs flattened into lists of
+/// their entries sorted by log index.
+#[derive(Archive, Deserialize)]
+struct Fields<A, T> {
+    log: Vec<(Change<T>, EarliestDeletion)>,
+    root: LogIndex,
+    version: Vec<Timestamp<A>>,
+    compacted: Vec<Timestamp<A>>,
+    next_indices: Vec<(LogIndex, Option<LogIndex>)>,
+    references: Vec<(LogIndex, Option<LogIndex>)>,
+    authors: Vec<(LogIndex, A)>,
+    index_shifts: Vec<(LogIndex, isize)>,
+}
+
+/// Like `Fields`, but borrowing the log instead of copying it.
+#[derive(Archive, Serialize)]
+#[rkyv(as = ArchivedFields<A, T>)]
+struct FieldsRef<'a, A, T>
+where
+    A: Archive,
+    T: Archive,
+    Inline: ArchiveWith<
+        &'a Vec<(Change<T>, EarliestDeletion)>,
+        Archived = Archived<Vec<(Change<T>, EarliestDeletion)>>,
+    >,
+{
+    #[rkyv(with = Inline)]
+    log: &'a Vec<(Change<T>, EarliestDeletion)>,
+    root: LogIndex,
+    version: Vec<Timestamp<A>>,
+    compacted: Vec<Timestamp<A>>,
+    next_indices: Vec<(LogIndex, Option<LogIndex>)>,
+    references: Vec<(LogIndex, Option<LogIndex>)>,
+    authors: Vec<(LogIndex, A)>,
+    index_shifts: Vec<(LogIndex, isize)>,
+}
+
+impl<A, T> Chronofold<A, T>
+where
+    A: Author + Archive + for<'a> Serialize<HighSerializer<AlignedVec, ArenaHandle<'a>, Error>>,
+    T: Archive + for<'a> Serialize<HighSerializer<AlignedVec, ArenaHandle<'a>, Error>>,
+{
+    /// Returns the chronofold as an archive, which can be read without
+    /// deserializing it.
+    ///
+    /// See `ArchivedChronofold`. This is only available for chronofolds kept
+    /// in memory.
+    pub fn to_archive(&self) -> AlignedVec {
+        let storage = &self.storage;
+        let fields = FieldsRef {
+            log: &storage.log,
+            root: self.root,
+            version: self.version.iter().collect(),
+            compacted: self.compacted.iter().collect(),
+            next_indices: storage.next_indices.iter().map(|(k, v)| (*k, v)).collect(),
+            references: storage.references.iter().map(|(k, v)| (*k, v)).collect(),
+            authors: storage.authors.iter().map(|(k, v)| (*k, *v)).collect(),
+            index_shifts: storage
+                .index_shifts
+                .iter()
+                .map(|(k, v)| (*k, v.0))
+                .collect(),
+        };
+        rkyv::to_bytes::<Error>(&fields).expect("serializing into memory doesn't fail")
+    }
+}
+
+/// A read-only view of a chronofold archived by `Chronofold::to_archive`.
+///
+/// The archive is read in place, e.g. from a memory-mapped file, so opening
+/// it doesn't require allocations proportional to its size. Use
+/// `to_chronofold` to start editing.
+///
+/// The archive has to be aligned to 16 bytes, which holds for `AlignedVec`
+/// and memory-mapped files.
+///
+/// ```rust
+/// use chronofold::{ArchivedChronofold, Chronofold};
+///
+/// let mut cfold = Chronofold::<u8, char>::default();
+/// cfold.session(1).extend("Hello!".chars());
+/// let bytes = cfold.to_archive();
+///
+/// let archived = ArchivedChronofold::<u8, char>::access(&bytes).unwrap();
+/// let text: String = archived.iter().map(|(c, _)| c.to_native()).collect();
+/// assert_eq!("Hello!", text);
+///
+/// let mut cfold = archived.to_chronofold().unwrap();
+/// cfold.session(1).push_back('?');
+/// ```
+pub struct ArchivedChronofold<'a, A: Archive, T: Archive> {
+    fields: &'a ArchivedFields<A, T>,
+    _marker: PhantomData<(A, T)>,
+}
+
+impl<'a, A, T> ArchivedChronofold<'a, A, T>
+where
+    A: Author + Archive,
+    T: Archive,
+    A::Archived:
+        for<'b> CheckBytes<HighValidator<'b, Error>> + Deserialize<A, Strategy<Pool, Error>>,
+    T::Archived:
+        for<'b> CheckBytes<HighValidator<'b, Error>> + Deserialize<T, Strategy<Pool, Error>>,
+{
+    /// Validates an archive and returns a view of it.
+    ///
+    /// This only makes sure that reading the archive is memory safe. Like
+    /// deserialized chronofolds, converting it by `to_chronofold` checks its
+    /// invariants.
+    pub fn access(bytes: &'a [u8]) -> Result<Self, ArchiveError> {
+        let fields = rkyv::access::<ArchivedFields<A, T>, Error>(bytes)
+            .map_err(|err| ArchiveError::Decode(err.to_string()))?;
+        Ok(Self {
+            fields,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns `true` if the chronofold contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Returns the number of elements in the chronofold.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns a reference to a change in the chronofold's log.
+    ///
+    /// If `index` is out of bounds, `None` is returned.
+    pub fn get(&self, index: LogIndex) -> Option<&'a ArchivedChange<T>> {
+        self.fields.log.get(index.0).map(|e| &e.0)
+    }
+
+    /// Returns an iterator over elements and their log indices in causal
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a T::Archived, LogIndex)> + 'a {
+        let fields = self.fields;
+        let mut current = next_index(fields, native(&fields.root));
+        // Malformed archives might contain cycles.
+        let mut steps = 0;
+        std::iter::from_fn(move || {
+            while let Some(idx) = current {
+                steps += 1;
+                if steps > fields.log.len() {
+                    return None;
+                }
+                current = next_index(fields, idx);
+                let entry = fields.log.get(idx.0)?;
+                if let (ArchivedChange::Insert(value), None) = (&entry.0, entry.1.as_ref()) {
+                    return Some((value, idx));
+                }
+            }
+            None
+        })
+    }
+
+    /// Returns a vector clock representing the version of this chronofold.
+    pub fn version(&self) -> Version<A> {
+        let mut version = Version::new();
+        for t in self.fields.version.iter() {
+            version.inc(&Timestamp(native(&t.0), deserialize(&t.1)));
+        }
+        version
+    }
+
+    /// Deserializes the archive into a chronofold, which can be edited.
+    pub fn to_chronofold(&self) -> Result<Chronofold<A, T>, ArchiveError> {
+        let fields: Fields<A, T> = rkyv::deserialize::<_, Error>(self.fields)
+            .map_err(|err| ArchiveError::Decode(err.to_string()))?;
+
+        let mut storage = MemoryStorage::new();
+        storage.log = fields.log;
+        for (idx, next) in fields.next_indices {
+            storage.next_indices.set(idx, next);
+        }
+        for (idx, reference) in fields.references {
+            storage.references.set(idx, reference);
+        }
+        for (idx, author) in fields.authors {
+            storage.authors.set(idx, author);
+        }
+        for (idx, shift) in fields.index_shifts {
+            storage.index_shifts.set(idx, IndexShift(shift));
+        }
+
+        let mut cfold = Chronofold::empty_in(storage);
+        cfold.root = fields.root;
+        for t in fields.version {
+            cfold.version.inc(&t);
+        }
+        for t in fields.compacted {
+            cfold.compacted.inc(&t);
+        }
+        cfold.check_invariants().map_err(ArchiveError::Invariant)?;
+        Ok(cfold)
+    }
+}
+
+fn native(index: &ArchivedLogIndex) -> LogIndex {
+    LogIndex(index.0.to_native() as usize)
+}
+
+fn deserialize<A: Archive>(author: &A::Archived) -> A
+where
+    A::Archived: Deserialize<A, Strategy<Pool, Error>>,
+{
+    rkyv::deserialize::<A, Error>(author).expect("authors are plain values")
+}
+
+/// Returns the next log index (causal order), see `Chronofold::index_after`.
+fn next_index<A, T>(fields: &ArchivedFields<A, T>, index: LogIndex) -> Option<LogIndex>
+where
+    A: Archive,
+    T: Archive,
+{
+    match fields
+        .next_indices
+        .binary_search_by_key(&index, |e| native(&e.0))
+    {
+        Ok(i) => fields.next_indices[i].1.as_ref().map(native),
+        Err(_) => Some(LogIndex(index.0 + 1)),
+    }
+}
+
+/// Represents errors that can occur when reading an `ArchivedChronofold`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum ArchiveError {
+    /// The bytes are not a valid archive.
+    Decode(String),
+    /// The archived chronofold violates an invariant.
+    Invariant(InvariantError),
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ArchiveError::*;
+        match self {
+            Decode(err) => write!(f, "invalid archive: {err}"),
+            Invariant(err) => write!(f, "invalid chronofold: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
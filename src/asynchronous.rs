@@ -0,0 +1,121 @@
+//! Adapters connecting shared chronofolds to asynchronous code.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::channel::mpsc::{self, UnboundedReceiver};
+use futures::{Sink, Stream};
+
+use crate::{Author, BufferError, CausalBuffer, Op, SharedChronofold};
+
+impl<A, T> SharedChronofold<A, T>
+where
+    A: Author + Send + 'static,
+    T: Clone + Send + 'static,
+{
+    /// Returns a sink applying ops to the chronofold.
+    pub fn sink(&self) -> OpSink<A, T> {
+        OpSink {
+            cfold: self.clone(),
+            buffer: CausalBuffer::new(),
+        }
+    }
+
+    /// Returns a stream of the ops produced by `edit` from now on.
+    ///
+    /// Ops applied by `apply` or an `OpSink` are not part of the stream. It
+    /// ends when all handles to the chronofold are dropped.
+    ///
+    /// ```rust
+    /// use chronofold::{Chronofold, SharedChronofold};
+    /// use futures::executor::block_on;
+    /// use futures::StreamExt;
+    ///
+    /// let alice = SharedChronofold::new(Chronofold::<u8, char>::default());
+    /// let bob = SharedChronofold::new(alice.read().clone());
+    ///
+    /// let ops = alice.local_ops();
+    /// alice.edit(1, |session| session.extend("Hi!".chars()));
+    /// drop(alice);
+    ///
+    /// block_on(ops.map(Ok).forward(bob.sink())).unwrap();
+    /// assert_eq!("Hi!", format!("{}", *bob.read()));
+    /// ```
+    pub fn local_ops(&self) -> LocalOps<A, T> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.subscribe_with(true, Box::new(move |op| sender.unbounded_send(op).is_ok()));
+        LocalOps { receiver }
+    }
+}
+
+/// A sink applying ops to a shared chronofold.
+///
+/// Ops whose causal dependencies are missing are kept until those arrive, ops
+/// applied before are ignored. Other errors, e.g.
+/// `ChronofoldError::Equivocation`, are returned. See `CausalBuffer`.
+///
+/// Sending an op locks the chronofold for writing, blocking the current
+/// thread until readers release their guards. Keep `SharedChronofold::read`
+/// guards short-lived when using the sink on an executor.
+///
+/// This struct is created by the `sink` method on `SharedChronofold`.
+#[derive(Debug)]
+pub struct OpSink<A, T> {
+    cfold: SharedChronofold<A, T>,
+    buffer: CausalBuffer<A, T>,
+}
+
+impl<A: Author, T> OpSink<A, T> {
+    /// Sets the maximum number of ops kept until the ops they depend on
+    /// arrive.
+    ///
+    /// See `CausalBuffer::with_max_pending`.
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.buffer = self.buffer.with_max_pending(max_pending);
+        self
+    }
+}
+
+impl<A, T> Sink<Op<A, T>> for OpSink<A, T>
+where
+    A: Author + Send + 'static,
+    T: Clone + PartialEq + Send + 'static,
+{
+    type Error = BufferError<A, T>;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// Applies `op` and the pending ops it makes applicable.
+    ///
+    /// This blocks on the chronofold's lock, see `OpSink`.
+    fn start_send(self: Pin<&mut Self>, op: Op<A, T>) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        this.cfold.apply_buffered(&mut this.buffer, [op])
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A stream of the ops produced locally on a shared chronofold.
+///
+/// This struct is created by the `local_ops` method on `SharedChronofold`.
+#[derive(Debug)]
+pub struct LocalOps<A, T> {
+    receiver: UnboundedReceiver<Op<A, T>>,
+}
+
+impl<A, T> Stream for LocalOps<A, T> {
+    type Item = Op<A, T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
@@ -0,0 +1,134 @@
+//! Inspects chronofolds serialized as JSON.
+//!
+//! Files contain either a `Chronofold<u64, char>` or a list of
+//! `Op<u64, char>`, which are applied to an empty chronofold.
+
+use std::fs;
+use std::mem;
+use std::process;
+
+use chronofold::{CausalBuffer, Chronofold, Op, Version};
+
+type Document = Chronofold<u64, char>;
+
+const USAGE: &str = "\
+usage: chronofold <command> <file> [<file>]
+
+commands:
+    show <file>         print the text
+    log <file>          print the log
+    version <file>      print the version
+    blame <file>        print the text with the authors of each line
+    diff <old> <new>    print a unified diff between two replicas
+    merge <a> <b>       print the JSON of both replicas merged
+    check <file>        validate the file";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(output) => print!("{output}"),
+        Err(err) => {
+            eprintln!("error: {err}");
+            process::exit(1);
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<String, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args[..] {
+        ["show", path] => Ok(format!("{}\n", load(path)?)),
+        ["log", path] => Ok(load(path)?.formatted_log()),
+        ["version", path] => Ok(load(path)?
+            .version()
+            .iter()
+            .map(|t| format!("{}\t{}\n", t.1, t.0))
+            .collect()),
+        ["blame", path] => Ok(blame(&load(path)?)),
+        ["diff", old, new] => {
+            let old = load(old)?;
+            let new = load(new)?;
+            let merged = merge(old.clone(), &new)?;
+            Ok(merged.to_unified_diff(old.version(), new.version()))
+        }
+        ["merge", a, b] => {
+            let merged = merge(load(a)?, &load(b)?)?;
+            serde_json::to_string(&merged)
+                .map(|json| json + "\n")
+                .map_err(|err| err.to_string())
+        }
+        ["check", path] => {
+            let cfold = load(path)?;
+            Ok(format!(
+                "ok: {} elements, {} ops\n",
+                cfold.len(),
+                cfold.iter_ops::<_, &char>(..).count()
+            ))
+        }
+        _ => Err(USAGE.to_owned()),
+    }
+}
+
+/// Loads a chronofold or a list of ops from a JSON file.
+///
+/// Deserializing a chronofold checks its invariants.
+fn load(path: &str) -> Result<Document, String> {
+    let json = fs::read(path).map_err(|err| format!("{path}: {err}"))?;
+    let cfold_err = match serde_json::from_slice::<Document>(&json) {
+        Ok(cfold) => return Ok(cfold),
+        Err(err) => err,
+    };
+    match serde_json::from_slice::<Vec<Op<u64, char>>>(&json) {
+        Ok(ops) => apply_all(Chronofold::empty(), ops).map_err(|err| format!("{path}: {err}")),
+        Err(_) => Err(format!("{path}: {cfold_err}")),
+    }
+}
+
+/// Applies ops in causal order, regardless of the order they are given in.
+fn apply_all(mut cfold: Document, ops: Vec<Op<u64, char>>) -> Result<Document, String> {
+    let mut pending = CausalBuffer::new().with_max_pending(usize::MAX);
+    pending
+        .apply(&mut cfold, ops)
+        .map_err(|err| err.to_string())?;
+    if let Some(op) = pending.iter().next() {
+        return Err(format!(
+            "{} ops with missing dependencies, e.g. {}",
+            pending.len(),
+            op.id
+        ));
+    }
+    Ok(cfold)
+}
+
+fn merge(a: Document, b: &Document) -> Result<Document, String> {
+    apply_all(
+        a,
+        b.iter_newer_ops::<&char>(&Version::new())
+            .map(Op::cloned)
+            .collect(),
+    )
+}
+
+/// Returns the text, each line prefixed by the authors of its characters.
+fn blame(cfold: &Document) -> String {
+    let mut result = String::new();
+    let mut line = String::new();
+    let mut authors: Vec<u64> = vec![];
+    let mut elements = cfold.iter().peekable();
+    while let Some((c, idx)) = elements.next() {
+        let author = cfold.timestamp(idx).unwrap().1;
+        if !authors.contains(&author) {
+            authors.push(author);
+        }
+        line.push(*c);
+        if *c == '\n' || elements.peek().is_none() {
+            authors.sort_unstable();
+            let authors: Vec<String> = authors.drain(..).map(|a| a.to_string()).collect();
+            result += &format!("{:<12} | {}", authors.join(","), mem::take(&mut line));
+            if !result.ends_with('\n') {
+                result.push('\n');
+            }
+        }
+    }
+    result
+}
@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::{Author, BufferError, Chronofold, ChronofoldError, LogIndex, Op, Storage, Timestamp};
+
+/// The default maximum number of ops kept until their dependencies arrive.
+const DEFAULT_MAX_PENDING: usize = 100_000;
+
+/// A buffer applying ops in causal order, regardless of the order they
+/// arrive in.
+///
+/// Ops referencing unknown changes or having timestamps from the future are
+/// kept until the ops they depend on are applied. Each op is only retried
+/// once something it depends on changed. A buffer is meant to be used with a
+/// single chronofold.
+///
+/// ```rust
+/// use chronofold::{CausalBuffer, Chronofold, Op};
+///
+/// let mut source = Chronofold::<u8, char>::default();
+/// source.session(1).extend("Hi!".chars());
+/// let ops: Vec<Op<u8, char>> = source.iter_ops(..).map(Op::cloned).collect();
+///
+/// let mut cfold = Chronofold::<u8, char>::empty();
+/// let mut buffer = CausalBuffer::new();
+/// buffer.apply(&mut cfold, ops.into_iter().rev()).unwrap();
+/// assert!(buffer.is_empty());
+/// assert_eq!("Hi!", format!("{cfold}"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct CausalBuffer<A, T> {
+    max_pending: usize,
+    len: usize,
+    /// The log length of the chronofold after the last call to `apply`.
+    /// Changes applied in other ways might have made waiting ops
+    /// applicable.
+    seen_len: usize,
+    /// Ops waiting for the change with the given timestamp.
+    waiting: BTreeMap<Timestamp<A>, Vec<Op<A, T>>>,
+    /// Ops to retry once the chronofold's next timestamp index reaches the
+    /// given one.
+    retry: BTreeMap<LogIndex, Vec<Op<A, T>>>,
+}
+
+impl<A: Author, T> CausalBuffer<A, T> {
+    /// Constructs a new, empty buffer.
+    pub fn new() -> Self {
+        Self {
+            max_pending: DEFAULT_MAX_PENDING,
+            len: 0,
+            seen_len: 0,
+            waiting: BTreeMap::new(),
+            retry: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the maximum number of ops kept until the ops they depend on
+    /// arrive.
+    ///
+    /// This bounds the memory a peer can make the buffer use by sending ops
+    /// with unknown references.
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = max_pending;
+        self
+    }
+
+    /// Returns the number of ops waiting for their dependencies.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no ops are waiting.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over the waiting ops.
+    pub fn iter(&self) -> impl Iterator<Item = &Op<A, T>> {
+        self.waiting.values().chain(self.retry.values()).flatten()
+    }
+
+    /// Applies `ops` to `cfold`, followed by the waiting ops this makes
+    /// applicable.
+    ///
+    /// Ops applied before are ignored, ops whose dependencies are missing
+    /// are kept. If an op is rejected for other reasons, it is dropped and
+    /// `BufferError::Apply` is returned, the remaining ops are kept. If more
+    /// ops than allowed by `with_max_pending` are waiting, all of them are
+    /// discarded and `BufferError::TooManyPending` is returned.
+    pub fn apply<S, I>(
+        &mut self,
+        cfold: &mut Chronofold<A, T, S>,
+        ops: I,
+    ) -> Result<(), BufferError<A, T>>
+    where
+        S: Storage<A, T>,
+        T: Clone + PartialEq,
+        I: IntoIterator<Item = Op<A, T>>,
+    {
+        let mut ready: VecDeque<Op<A, T>> = ops.into_iter().collect();
+        if cfold.storage.len() != self.seen_len {
+            let known: Vec<Timestamp<A>> = self
+                .waiting
+                .keys()
+                .filter(|t| cfold.version().contains(t))
+                .copied()
+                .collect();
+            for t in known {
+                self.wake(&t, &mut ready);
+            }
+        }
+        // Ops left over after a rejected op are retried as well.
+        self.wake_retries(cfold.next_timestamp_index(), &mut ready);
+
+        while let Some(op) = ready.pop_front() {
+            let id = op.id;
+            match cfold.apply_idempotent(op) {
+                Ok(_) => {
+                    self.wake(&id, &mut ready);
+                    self.wake_retries(cfold.next_timestamp_index(), &mut ready);
+                }
+                Err(ChronofoldError::UnknownReference(op)) => {
+                    let reference = *op.payload.reference().unwrap();
+                    self.waiting.entry(reference).or_default().push(op);
+                    self.len += 1;
+                }
+                Err(ChronofoldError::FutureTimestamp(op)) => {
+                    self.retry.entry(op.id.0).or_default().push(op);
+                    self.len += 1;
+                }
+                Err(err) => {
+                    // Retried on the next call, whatever their timestamps.
+                    let len = ready.len();
+                    self.retry.entry(LogIndex(0)).or_default().extend(ready);
+                    self.len += len;
+                    self.seen_len = cfold.storage.len();
+                    return Err(BufferError::Apply(err));
+                }
+            }
+        }
+        self.seen_len = cfold.storage.len();
+
+        if self.len > self.max_pending {
+            let count = self.len;
+            self.clear();
+            return Err(BufferError::TooManyPending(count));
+        }
+        Ok(())
+    }
+
+    /// Discards all waiting ops.
+    pub fn clear(&mut self) {
+        self.waiting.clear();
+        self.retry.clear();
+        self.len = 0;
+    }
+
+    /// Moves the ops waiting for `id` to `ready`.
+    fn wake(&mut self, id: &Timestamp<A>, ready: &mut VecDeque<Op<A, T>>) {
+        if let Some(ops) = self.waiting.remove(id) {
+            self.len -= ops.len();
+            ready.extend(ops);
+        }
+    }
+
+    /// Moves the ops to retry at `next_index` or earlier to `ready`.
+    fn wake_retries(&mut self, next_index: LogIndex, ready: &mut VecDeque<Op<A, T>>) {
+        while let Some(entry) = self.retry.first_entry() {
+            if *entry.key() > next_index {
+                break;
+            }
+            let ops = entry.remove();
+            self.len -= ops.len();
+            ready.extend(ops);
+        }
+    }
+}
+
+impl<A: Author, T> Default for CausalBuffer<A, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{Author, Change, Chronofold, LogIndex, MemoryStorage, Storage, Timestamp, Version};
+
+impl<A: Author, T> Chronofold<A, T> {
+    /// Removes deleted elements from the log, as far as this is safe.
+    ///
+    /// `stable` has to be causally stable, i.e. every replica has seen it and
+    /// all ops not covered by it are causally newer. Elements inserted and
+    /// deleted by changes covered by `stable` are removed along with their
+    /// deletions. Other changes referencing them are attached to the closest
+    /// remaining change they transitively reference, which keeps the order of
+    /// elements intact.
+    ///
+    /// Changes not covered by `stable` can still be applied afterwards, as
+    /// long as they don't reference any removed change. `Session::extend` and
+    /// `Session::splice` never do this, as they anchor changes on elements
+    /// that are not deleted. Ops covered by `stable` are treated as already
+    /// applied, even if they were removed.
+    ///
+    /// Note that this discards history: log indices change, ops of removed
+    /// changes are no longer available and remaining ops may reference
+    /// different changes than before.
+    ///
+    /// This is only available for chronofolds kept in memory.
+    pub fn compact(&mut self, stable: &Version<A>) {
+        // Never consider changes as compacted that we haven't seen yet.
+        for t in stable.iter() {
+            if let Some(idx) = self.version.get(&t.1) {
+                let idx = LogIndex(usize::min(t.0 .0, idx.0));
+                self.compacted.inc(&Timestamp(idx, t.1));
+            }
+        }
+
+        let len = self.storage.len();
+        let ids: Vec<Timestamp<A>> = (0..len)
+            .map(|i| self.timestamp(LogIndex(i)).unwrap())
+            .collect();
+        let references: Vec<Option<LogIndex>> = (0..len)
+            .map(|i| self.storage.reference(LogIndex(i)))
+            .collect();
+        let next_indices: Vec<Option<LogIndex>> = (0..len)
+            .map(|i| self.storage.next_index(LogIndex(i)))
+            .collect();
+        let is_stable: Vec<bool> = ids.iter().map(|id| self.compacted.contains(id)).collect();
+        let is_delete: Vec<bool> = self
+            .iter_changes()
+            .map(|(change, _)| matches!(change, Change::Delete))
+            .collect();
+        let mut children: Vec<Vec<usize>> = vec![vec![]; len];
+        for (i, reference) in references.iter().enumerate() {
+            if let Some(reference) = reference {
+                children[reference.0].push(i);
+            }
+        }
+
+        // Start with all stable inserts deleted by a stable deletion and all
+        // stable deletions of those.
+        let mut removed = vec![false; len];
+        for i in (0..len).filter(|i| is_delete[*i] && is_stable[*i]) {
+            let target = references[i].expect("deletes must have a reference").0;
+            if is_stable[target] && matches!(self[LogIndex(target)], Change::Insert(_)) {
+                removed[target] = true;
+            }
+        }
+        for i in (0..len).filter(|i| is_delete[*i] && is_stable[*i]) {
+            removed[i] = removed[references[i].unwrap().0];
+        }
+
+        // Keep changes having children we can neither remove nor attach to
+        // another change. This includes deletions of the change and changes
+        // which are not stable, as those might be referenced by concurrent
+        // changes we haven't seen yet.
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for i in 0..len {
+                let keep = removed[i]
+                    && (children[i]
+                        .iter()
+                        .any(|&c| !removed[c] && (is_delete[c] || !is_stable[c]))
+                        || is_delete[i] && !removed[references[i].unwrap().0]);
+                if keep {
+                    removed[i] = false;
+                    changed = true;
+                }
+            }
+        }
+
+        let mut new_indices = vec![None; len];
+        for (new_index, i) in (0..len).filter(|i| !removed[*i]).enumerate() {
+            new_indices[i] = Some(LogIndex(new_index));
+        }
+        let remaining = |mut idx: Option<LogIndex>, follow: &[Option<LogIndex>]| {
+            while let Some(i) = idx.filter(|i| removed[i.0]) {
+                idx = follow[i.0];
+            }
+            idx.map(|i| new_indices[i.0].unwrap())
+        };
+
+        let mut storage = MemoryStorage::new();
+        let old_log = std::mem::take(&mut self.storage.log);
+        for (i, (change, deletion)) in old_log.into_iter().enumerate() {
+            let new_index = match new_indices[i] {
+                Some(new_index) => new_index,
+                None => continue,
+            };
+            let deletion = deletion
+                .map(|d| new_indices[d.0].expect("deletions of remaining changes are not removed"));
+            storage.push(
+                change,
+                ids[i],
+                remaining(references[i], &references),
+                remaining(next_indices[i], &next_indices),
+            );
+            storage.set_earliest_deletion(new_index, deletion);
+        }
+
+        self.root = new_indices[self.root.0].expect("roots are never removed");
+        self.storage = storage;
+    }
+}
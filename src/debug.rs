@@ -1,23 +1,82 @@
+use std::collections::BTreeMap;
 use std::fmt;
 
-use crate::{Author, Chronofold, LogIndex};
+use crate::{Author, Change, Chronofold, LogIndex, Storage};
 
-impl<A: Author, T: fmt::Debug> Chronofold<A, T> {
+/// Fill colors for the nodes of different authors in `Chronofold::to_dot`.
+const COLORS: [&str; 8] = [
+    "#8dd3c7", "#ffffb3", "#bebada", "#fb8072", "#80b1d3", "#fdb462", "#b3de69", "#fccde5",
+];
+
+impl<A: Author, T: fmt::Debug, S: Storage<A, T>> Chronofold<A, T, S> {
     pub fn formatted_log(&self) -> String {
         let mut result = format!(
             "{:<4} | {:<4} | {:<4} | {:<4} | change\n",
             "idx", "ref", "next", "del"
         );
-        for (idx, change) in self.log.iter().enumerate() {
-            let idx = LogIndex(idx);
-            let ref_ = format_option(self.references.get(&idx));
-            let next = format_option(self.next_indices.get(&idx));
-            let del = format_option(change.1);
-            let change = &change.0;
+        for idx in (0..self.storage.len()).map(LogIndex) {
+            let entry = self.storage.get(idx).unwrap();
+            let ref_ = format_option(self.storage.reference(idx));
+            let next = format_option(self.storage.next_index(idx));
+            let del = format_option(entry.1);
+            let change = &entry.0;
             result += &format!("{idx:<4} | {ref_:<4} | {next:<4} | {del:<4} | {change:?}\n");
         }
         result
     }
+
+    /// Returns the causal tree in the Graphviz DOT language.
+    ///
+    /// Solid edges point from referenced entries to the entries referencing
+    /// them, dashed edges follow the next indices, i.e. the order of the
+    /// weave. Nodes are colored by author. Deleted entries are drawn dashed
+    /// and gray, deletions as boxes.
+    pub fn to_dot(&self) -> String {
+        let mut colors = BTreeMap::new();
+        for idx in (0..self.storage.len()).map(LogIndex) {
+            let n = colors.len();
+            colors
+                .entry(self.timestamp(idx).unwrap().1)
+                .or_insert(COLORS[n % COLORS.len()]);
+        }
+
+        let mut result = "digraph chronofold {\n    node [style=filled];\n".to_owned();
+        for i in 0..self.storage.len() {
+            let idx = LogIndex(i);
+            let (change, deletion) = &*self.storage.get(idx).unwrap();
+            let id = self.timestamp(idx).unwrap();
+            let label = escape(&format!("{idx}\n{id}\n{change:?}"));
+            let color = colors[&id.1];
+            let mut attributes = format!("label=\"{label}\", fillcolor=\"{color}\"");
+            if let Change::Delete = change {
+                attributes += ", shape=box";
+            }
+            if deletion.is_some() {
+                attributes += ", style=\"filled,dashed\", fontcolor=gray";
+            }
+            result += &format!("    {i} [{attributes}];\n");
+        }
+        for idx in (0..self.storage.len()).map(LogIndex) {
+            if let Some(reference) = self.storage.reference(idx) {
+                result += &format!("    {reference} -> {idx};\n");
+            }
+        }
+        for idx in (0..self.storage.len()).map(LogIndex) {
+            if let Some(next) = self.storage.next_index(idx) {
+                result +=
+                    &format!("    {idx} -> {next} [style=dashed, color=blue, constraint=false];\n");
+            }
+        }
+        result += "}\n";
+        result
+    }
+}
+
+/// Escapes a string for use in a quoted DOT label.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
 }
 
 fn format_option<T: fmt::Display>(option: Option<T>) -> String {
@@ -0,0 +1,161 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::{Author, Change, Chronofold, EarliestDeletion, LogIndex, Storage};
+
+/// A hash over all changes in a chronofold's log.
+///
+/// See `Chronofold::digest`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Digest(pub u64);
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl<A: Author + Hash, T: Hash, S: Storage<A, T>> Chronofold<A, T, S> {
+    /// Returns a hash over the ops of all changes in the log.
+    ///
+    /// Replicas having applied the same ops have equal digests, regardless of
+    /// the order they applied them in. The digest is the same on all
+    /// platforms, as long as `A` and `T` hash the same way.
+    ///
+    /// Note that `compact` removes ops from the log and changes references,
+    /// so compacted replicas generally have different digests.
+    pub fn digest(&self) -> Digest {
+        Digest(
+            (0..self.storage.len())
+                .map(|idx| self.op_hash(LogIndex(idx)))
+                .fold(0, u64::wrapping_add),
+        )
+    }
+
+    /// Returns the hash of the op of the change at `idx`.
+    pub(crate) fn op_hash(&self, idx: LogIndex) -> u64 {
+        let mut hasher = StableHasher::default();
+        self.timestamp(idx).hash(&mut hasher);
+        match &self.storage.get(idx).unwrap().0 {
+            Change::Root => 0u8.hash(&mut hasher),
+            Change::Insert(value) => {
+                1u8.hash(&mut hasher);
+                value.hash(&mut hasher);
+            }
+            Change::Delete => 2u8.hash(&mut hasher),
+        }
+        self.storage
+            .reference(idx)
+            .map(|r| self.timestamp(r))
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<A: Author, T: PartialEq, S: Storage<A, T>> Chronofold<A, T, S> {
+    /// Returns `true` if both chronofolds contain the same elements.
+    ///
+    /// Unlike `==`, this ignores the logs and storages of both chronofolds.
+    pub fn content_eq<B, R>(&self, other: &Chronofold<B, T, R>) -> bool
+    where
+        B: Author,
+        R: Storage<B, T>,
+    {
+        let mut ours = self.iter_element_indices(..);
+        let mut theirs = other.iter_element_indices(..);
+        loop {
+            match (ours.next(), theirs.next()) {
+                (None, None) => return true,
+                (Some(a), Some(b))
+                    if same_change(
+                        self.storage.get(a).as_deref(),
+                        other.storage.get(b).as_deref(),
+                    ) => {}
+                _ => return false,
+            }
+        }
+    }
+}
+
+/// Returns `true` if both entries exist and hold the same change.
+fn same_change<T: PartialEq>(
+    a: Option<&(Change<T>, EarliestDeletion)>,
+    b: Option<&(Change<T>, EarliestDeletion)>,
+) -> bool {
+    matches!((a, b), (Some((a, _)), Some((b, _))) if a == b)
+}
+
+/// A 64-bit FNV-1a hasher, which hashes integers in little-endian byte order.
+///
+/// Unlike the hashers in `std`, its output is guaranteed to be the same on
+/// all platforms and Rust versions.
+pub(crate) struct StableHasher(u64);
+
+impl StableHasher {
+    /// Constructs a hasher whose output differs from the default one's.
+    pub(crate) fn with_seed(seed: u64) -> Self {
+        let mut hasher = Self::default();
+        hasher.write_u64(seed);
+        hasher
+    }
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16)
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32)
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128)
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as u64)
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::fmt;
 
-use crate::{Chronofold, LogIndex};
+use crate::{Chronofold, LogIndex, MemoryStorage};
 
 /// A trait alias to reduce redundancy in type declarations.
 pub trait Author:
@@ -25,8 +25,13 @@ impl<T> Author for T where
 /// is consistent with cause-effect ordering. That is, if a timestamp is
 /// greater than another, its associated event either happened after the other
 /// or was concurrent.
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Timestamp<A>(pub LogIndex, pub A);
 
 impl<A: fmt::Display> fmt::Display for Timestamp<A> {
@@ -42,6 +47,7 @@ impl<A: fmt::Display> fmt::Display for Timestamp<A> {
 /// synchronized.
 #[derive(PartialEq, Eq, Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Op<A, T> {
     pub id: Timestamp<A>,
     pub payload: OpPayload<A, T>,
@@ -83,6 +89,7 @@ impl<A, T: Clone> Op<A, &T> {
 /// by a timestamp in the distributed operation.
 #[derive(PartialEq, Eq, Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum OpPayload<A, T> {
     Root,
     Insert(Option<Timestamp<A>>, T),
@@ -111,25 +118,25 @@ impl<A, T: Clone> OpPayload<A, &T> {
     }
 }
 
-pub trait IntoLocalValue<A, LocalValue> {
-    fn into_local_value(self, chronofold: &Chronofold<A, LocalValue>) -> LocalValue;
+pub trait IntoLocalValue<A, LocalValue, S = MemoryStorage<A, LocalValue>> {
+    fn into_local_value(self, chronofold: &Chronofold<A, LocalValue, S>) -> LocalValue;
 }
 
-pub trait FromLocalValue<'a, A, LocalValue> {
-    fn from_local_value(source: &'a LocalValue, chronofold: &Chronofold<A, LocalValue>) -> Self;
+pub trait FromLocalValue<'a, A, LocalValue, S = MemoryStorage<A, LocalValue>> {
+    fn from_local_value(source: &'a LocalValue, chronofold: &Chronofold<A, LocalValue, S>) -> Self;
 }
 
-impl<A, T, V> IntoLocalValue<A, T> for V
+impl<A, T, V, S> IntoLocalValue<A, T, S> for V
 where
     V: Into<T>,
 {
-    fn into_local_value(self, _chronofold: &Chronofold<A, T>) -> T {
+    fn into_local_value(self, _chronofold: &Chronofold<A, T, S>) -> T {
         self.into()
     }
 }
 
-impl<'a, A, T> FromLocalValue<'a, A, T> for &'a T {
-    fn from_local_value(source: &'a T, _chronofold: &Chronofold<A, T>) -> Self {
+impl<'a, A, T, S> FromLocalValue<'a, A, T, S> for &'a T {
+    fn from_local_value(source: &'a T, _chronofold: &Chronofold<A, T, S>) -> Self {
         source
     }
 }
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::{Op, OpPayload};
+use crate::{ClientId, LogIndex, Op, OpPayload};
 
 /// Represents errors that can occur when applying an op.
 ///
@@ -11,6 +11,20 @@ pub enum ChronofoldError<A, T> {
     UnknownReference(Op<A, T>),
     FutureTimestamp(Op<A, T>),
     ExistingTimestamp(Op<A, T>),
+    /// The op lacks a reference, or references a change it must not
+    /// reference (e.g. a delete referencing another delete).
+    InvalidReferenceKind(Op<A, T>),
+    /// The op deletes a root.
+    DeleteOfRoot(Op<A, T>),
+    /// The op is a root, but the chronofold is not empty.
+    MalformedRoot(Op<A, T>),
+    /// A different op with the same timestamp was applied already.
+    ///
+    /// This means the author of these ops is either buggy or malicious.
+    Equivocation {
+        existing: Op<A, T>,
+        received: Op<A, T>,
+    },
 }
 
 impl<A, T> fmt::Debug for ChronofoldError<A, T>
@@ -23,6 +37,16 @@ where
             UnknownReference(op) => ("UnknownReference", op),
             FutureTimestamp(op) => ("FutureTimestamp", op),
             ExistingTimestamp(op) => ("ExistingTimestamp", op),
+            InvalidReferenceKind(op) => ("InvalidReferenceKind", op),
+            DeleteOfRoot(op) => ("DeleteOfRoot", op),
+            MalformedRoot(op) => ("MalformedRoot", op),
+            Equivocation { existing, received } => {
+                return f
+                    .debug_struct("Equivocation")
+                    .field("existing", &existing.omit_value())
+                    .field("received", &received.omit_value())
+                    .finish();
+            }
         };
         f.debug_tuple(name).field(&op.omit_value()).finish()
     }
@@ -45,12 +69,96 @@ where
             ),
             FutureTimestamp(op) => write!(f, "future timestamp {}", op.id),
             ExistingTimestamp(op) => write!(f, "existing timestamp {}", op.id),
+            InvalidReferenceKind(op) => match op.payload.reference() {
+                Some(reference) => write!(f, "invalid reference {} in {}", reference, op.id),
+                None => write!(f, "missing reference in {}", op.id),
+            },
+            DeleteOfRoot(op) => write!(f, "deletion of root in {}", op.id),
+            MalformedRoot(op) => write!(f, "root {} in non-empty chronofold", op.id),
+            Equivocation { received, .. } => write!(f, "equivocation at {}", received.id),
         }
     }
 }
 
 impl<A, T> std::error::Error for ChronofoldError<A, T> where A: fmt::Debug + fmt::Display + Copy {}
 
+/// Represents errors that can occur when applying ops with a `CausalBuffer`.
+#[derive(PartialEq, Eq, Clone)]
+pub enum BufferError<A, T> {
+    /// An op was rejected for reasons other than missing dependencies.
+    Apply(ChronofoldError<A, T>),
+    /// This many ops were waiting for their dependencies, more than allowed.
+    /// They were discarded.
+    TooManyPending(usize),
+}
+
+impl<A, T> fmt::Debug for BufferError<A, T>
+where
+    A: fmt::Debug + fmt::Display + Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BufferError::Apply(err) => f.debug_tuple("Apply").field(err).finish(),
+            BufferError::TooManyPending(count) => {
+                f.debug_tuple("TooManyPending").field(count).finish()
+            }
+        }
+    }
+}
+
+impl<A, T> fmt::Display for BufferError<A, T>
+where
+    A: fmt::Debug + fmt::Display + Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BufferError::Apply(err) => write!(f, "rejected op: {err}"),
+            BufferError::TooManyPending(count) => {
+                write!(f, "{count} ops with missing dependencies")
+            }
+        }
+    }
+}
+
+impl<A, T> std::error::Error for BufferError<A, T> where A: fmt::Debug + fmt::Display + Copy {}
+
+/// Represents errors that can occur when relaying ops with a `Hub`.
+#[derive(PartialEq, Eq, Clone)]
+pub enum HubError<A, T> {
+    /// The client is not connected.
+    UnknownClient(ClientId),
+    /// An op sent by the client was rejected.
+    Apply(ChronofoldError<A, T>),
+}
+
+impl<A, T> fmt::Debug for HubError<A, T>
+where
+    A: fmt::Debug + fmt::Display + Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HubError::UnknownClient(client) => {
+                f.debug_tuple("UnknownClient").field(client).finish()
+            }
+            HubError::Apply(err) => f.debug_tuple("Apply").field(err).finish(),
+        }
+    }
+}
+
+impl<A, T> fmt::Display for HubError<A, T>
+where
+    A: fmt::Debug + fmt::Display + Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HubError::UnknownClient(client) => write!(f, "unknown client {client}"),
+            HubError::Apply(err) => write!(f, "rejected op: {err}"),
+        }
+    }
+}
+
+impl<A, T> std::error::Error for HubError<A, T> where A: fmt::Debug + fmt::Display + Copy {}
+
 impl<A, T> Op<A, T>
 where
     A: Copy,
@@ -70,3 +178,68 @@ where
 
 #[derive(Debug)]
 struct Omitted;
+
+/// Represents errors that can occur when applying a patch.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum PatchError {
+    /// The patch could not be parsed, starting at the given 1-based line.
+    Malformed(usize),
+    /// The listed hunks (1-based) don't match the current text.
+    Rejected(Vec<usize>),
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use PatchError::*;
+        match self {
+            Malformed(line) => write!(f, "malformed patch at line {line}"),
+            Rejected(hunks) => {
+                let hunks: Vec<String> = hunks.iter().map(|h| format!("#{h}")).collect();
+                write!(f, "hunks {} failed to apply", hunks.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// Represents violations of a chronofold's internal invariants.
+///
+/// See `Chronofold::check_invariants`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum InvariantError {
+    /// The root index doesn't point to a root change, or there is another
+    /// root.
+    InvalidRoot(LogIndex),
+    /// The timestamp of a log entry is missing, too large or not unique.
+    InvalidTimestamp(LogIndex),
+    /// A log entry's reference doesn't precede it, is missing although
+    /// required, present although not allowed or of the wrong kind.
+    InvalidReference(LogIndex),
+    /// A log entry's next index is out of bounds, points to a root or to an
+    /// entry that is already linked to, or the entry is not reachable.
+    InvalidNextIndex(LogIndex),
+    /// A log entry's earliest deletion doesn't match the deletions in the log.
+    InvalidDeletion(LogIndex),
+    /// A secondary log contains an entry beyond the end of the log.
+    ExcessEntry(LogIndex),
+    /// The version doesn't match the timestamps in the log.
+    InvalidVersion,
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use InvariantError::*;
+        match self {
+            InvalidRoot(idx) => write!(f, "invalid root at log index {idx}"),
+            InvalidTimestamp(idx) => write!(f, "invalid timestamp at log index {idx}"),
+            InvalidReference(idx) => write!(f, "invalid reference at log index {idx}"),
+            InvalidNextIndex(idx) => write!(f, "invalid next index at log index {idx}"),
+            InvalidDeletion(idx) => write!(f, "invalid deletion at log index {idx}"),
+            ExcessEntry(idx) => write!(f, "excess entry at log index {idx}"),
+            InvalidVersion => write!(f, "version doesn't match the log"),
+        }
+    }
+}
+
+impl std::error::Error for InvariantError {}
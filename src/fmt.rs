@@ -1,14 +1,14 @@
-use crate::{Author, Chronofold};
+use crate::{Author, Change, Chronofold, Storage};
 
 use std::fmt;
 
-impl<A: Author, T: fmt::Display> fmt::Display for Chronofold<A, T> {
+impl<A: Author, T: fmt::Display, S: Storage<A, T>> fmt::Display for Chronofold<A, T, S> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            self.iter_elements()
-                .fold("".to_owned(), |s, t| s + &t.to_string())
-        )
+        for idx in self.iter_element_indices(..) {
+            if let Some((Change::Insert(value), _)) = self.storage.get(idx).as_deref() {
+                write!(f, "{value}")?;
+            }
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::{Author, Chronofold, HubError, Op, Version};
+
+/// Identifies a client connected to a `Hub`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub struct ClientId(pub usize);
+
+impl fmt::Display for ClientId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A relay holding the authoritative chronofold for many clients.
+///
+/// Clients send their ops to the hub, which integrates them into its
+/// chronofold. For each client, the hub keeps track of the version the client
+/// is known to have, i.e. the last version it acknowledged plus the ops it
+/// sent. `missing_ops` returns all other ops, which the client should apply
+/// and then acknowledge.
+///
+/// ```rust
+/// use chronofold::{Chronofold, Hub};
+///
+/// let mut hub = Hub::new(Chronofold::<u8, char>::default());
+/// let mut cfold_alice = hub.chronofold().clone();
+/// let mut cfold_bob = hub.chronofold().clone();
+/// let alice = hub.connect(cfold_alice.version().clone());
+/// let bob = hub.connect(cfold_bob.version().clone());
+///
+/// let ops = {
+///     let mut session = cfold_alice.session(1);
+///     session.extend("Hi!".chars());
+///     session.iter_ops().map(|op| op.cloned()).collect::<Vec<_>>()
+/// };
+/// hub.receive(alice, ops).unwrap();
+///
+/// for op in hub.missing_ops(bob).unwrap() {
+///     cfold_bob.apply(op).unwrap();
+/// }
+/// hub.acknowledge(bob, cfold_bob.version()).unwrap();
+/// assert_eq!("Hi!", format!("{cfold_bob}"));
+/// assert!(hub.missing_ops(alice).unwrap().is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Hub<A, T> {
+    cfold: Chronofold<A, T>,
+    clients: BTreeMap<ClientId, Version<A>>,
+    next_client: usize,
+}
+
+impl<A: Author, T> Hub<A, T> {
+    /// Constructs a hub relaying the ops of `cfold`.
+    pub fn new(cfold: Chronofold<A, T>) -> Self {
+        Self {
+            cfold,
+            clients: BTreeMap::new(),
+            next_client: 0,
+        }
+    }
+
+    /// Returns the hub's chronofold.
+    pub fn chronofold(&self) -> &Chronofold<A, T> {
+        &self.cfold
+    }
+
+    /// Connects a client, which already has all ops covered by `version`.
+    pub fn connect(&mut self, version: Version<A>) -> ClientId {
+        let client = ClientId(self.next_client);
+        self.next_client += 1;
+        self.clients.insert(client, version);
+        client
+    }
+
+    /// Disconnects a client.
+    pub fn disconnect(&mut self, client: ClientId) -> Result<(), HubError<A, T>> {
+        match self.clients.remove(&client) {
+            Some(_) => Ok(()),
+            None => Err(HubError::UnknownClient(client)),
+        }
+    }
+
+    /// Returns the version `client` is known to have.
+    pub fn client_version(&self, client: ClientId) -> Result<&Version<A>, HubError<A, T>> {
+        self.clients
+            .get(&client)
+            .ok_or(HubError::UnknownClient(client))
+    }
+
+    /// Records that `client` has all ops covered by `version`.
+    pub fn acknowledge(
+        &mut self,
+        client: ClientId,
+        version: &Version<A>,
+    ) -> Result<(), HubError<A, T>> {
+        let known = self
+            .clients
+            .get_mut(&client)
+            .ok_or(HubError::UnknownClient(client))?;
+        for timestamp in version.iter() {
+            known.inc(&timestamp);
+        }
+        Ok(())
+    }
+}
+
+impl<A: Author, T: Clone + PartialEq> Hub<A, T> {
+    /// Applies ops sent by `client` in causal order.
+    ///
+    /// Ops the hub already knows are skipped. On error, ops preceding the
+    /// rejected one remain applied.
+    pub fn receive<I>(&mut self, client: ClientId, ops: I) -> Result<(), HubError<A, T>>
+    where
+        I: IntoIterator<Item = Op<A, T>>,
+    {
+        let known = self
+            .clients
+            .get_mut(&client)
+            .ok_or(HubError::UnknownClient(client))?;
+        for op in ops {
+            let id = op.id;
+            self.cfold.apply_idempotent(op).map_err(HubError::Apply)?;
+            known.inc(&id);
+        }
+        Ok(())
+    }
+
+    /// Returns the ops `client` is missing in log order.
+    pub fn missing_ops(&self, client: ClientId) -> Result<Vec<Op<A, T>>, HubError<A, T>> {
+        let version = self.client_version(client)?;
+        Ok(self
+            .cfold
+            .iter_newer_ops::<&T>(version)
+            .map(Op::cloned)
+            .collect())
+    }
+}
+
+impl<A: Author + Default, T> Default for Hub<A, T> {
+    fn default() -> Self {
+        Self::new(Chronofold::default())
+    }
+}
@@ -2,20 +2,28 @@ use std::fmt;
 use std::ops::{Add, Index, Sub};
 
 use crate::offsetmap::Offset;
-use crate::{Author, Change, Chronofold};
+use crate::{Author, Change, Chronofold, LendingStorage, Storage};
 
 /// An index in the log of the chronofold.
 ///
 /// The indices are `usize` as they are used to index into `Vec`s.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct LogIndex(pub usize);
 
-impl<A: Author, T> Index<LogIndex> for Chronofold<A, T> {
+impl<A: Author, T, S: LendingStorage<A, T>> Index<LogIndex> for Chronofold<A, T, S> {
     type Output = Change<T>;
 
     fn index(&self, index: LogIndex) -> &Self::Output {
-        &self.log[index.0].0
+        match self.storage.get_ref(index) {
+            Some((change, _)) => change,
+            None => panic!("log index {index} out of bounds"),
+        }
     }
 }
 
@@ -25,11 +33,11 @@ impl fmt::Display for LogIndex {
     }
 }
 
-impl<A: Author, T> Chronofold<A, T> {
+impl<A: Author, T, S: Storage<A, T>> Chronofold<A, T, S> {
     /// Returns the index of the last log entry (in log order).
     pub fn last_index(&self) -> Option<LogIndex> {
-        if !self.log.is_empty() {
-            Some(LogIndex(self.log.len() - 1))
+        if !self.storage.is_empty() {
+            Some(LogIndex(self.storage.len() - 1))
         } else {
             None
         }
@@ -42,12 +50,10 @@ impl<A: Author, T> Chronofold<A, T> {
     ///   1. `index` is the first index (causal order).
     ///   2. `index` is out of bounds.
     pub(crate) fn index_before(&self, index: LogIndex) -> Option<LogIndex> {
-        if matches!(self.log.get(index.0).map(|e| &e.0), Some(Change::Root)) {
+        if matches!(self.storage.get(index).as_deref(), Some((Change::Root, _))) {
             Some(index)
-        } else if let Some(reference) = self.references.get(&index) {
-            self.iter_log_indices_causal_range(reference..index)
-                .map(|(_, idx, _)| idx)
-                .last()
+        } else if let Some(reference) = self.storage.reference(index) {
+            self.iter_log_indices_causal_range(reference..index).last()
         } else {
             None
         }
@@ -60,7 +66,36 @@ impl<A: Author, T> Chronofold<A, T> {
     ///   1. `index` is the last index (causal order).
     ///   2. `index` is out of bounds.
     pub(crate) fn index_after(&self, index: LogIndex) -> Option<LogIndex> {
-        self.next_indices.get(&index)
+        self.storage.next_index(index)
+    }
+
+    /// Returns the log index of the previous element (causal order).
+    ///
+    /// Unlike `index_before`, this skips deleted elements and returns the
+    /// root's index if there is no element before `index`. It returns `None`
+    /// if `index` is out of bounds or the first index (causal order). The next
+    /// log index is considered to come after the last index (causal order).
+    ///
+    /// Sessions use this to find references for new changes, as anchoring
+    /// changes on deleted elements would prevent removing them by
+    /// `Chronofold::compact`.
+    pub(crate) fn element_before(&self, index: LogIndex) -> Option<LogIndex> {
+        let mut before = if index == self.next_log_index() {
+            // Recently added entries are usually close to the end.
+            let mut last = self.last_index()?;
+            while let Some(next) = self.index_after(last) {
+                last = next;
+            }
+            last
+        } else {
+            self.index_before(index)?
+        };
+        loop {
+            match *self.storage.get(before)? {
+                (Change::Root, _) | (Change::Insert(_), None) => return Some(before),
+                _ => before = self.index_before(before)?,
+            }
+        }
     }
 }
 
@@ -76,7 +111,9 @@ impl Default for RelativeNextIndex {
 
 impl Offset<LogIndex> for RelativeNextIndex {
     fn add(&self, value: &LogIndex) -> LogIndex {
-        LogIndex((value.0 as isize + self.0) as usize)
+        // Invalid offsets (e.g. from deserialized data) result in indices out
+        // of bounds, which `Chronofold::check_invariants` detects.
+        LogIndex((value.0 as isize).wrapping_add(self.0) as usize)
     }
 
     fn sub(a: &LogIndex, b: &LogIndex) -> Self {
@@ -96,7 +133,7 @@ impl Default for RelativeReference {
 
 impl Offset<LogIndex> for RelativeReference {
     fn add(&self, value: &LogIndex) -> LogIndex {
-        LogIndex((value.0 as isize + self.0) as usize)
+        LogIndex((value.0 as isize).wrapping_add(self.0) as usize)
     }
 
     fn sub(a: &LogIndex, b: &LogIndex) -> Self {
@@ -104,15 +141,25 @@ impl Offset<LogIndex> for RelativeReference {
     }
 }
 
+/// The difference between a log index and the log index of its timestamp.
+///
+/// This is negative for changes that were moved to smaller log indices by
+/// `Chronofold::compact`.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub(crate) struct IndexShift(pub usize);
+pub(crate) struct IndexShift(pub isize);
+
+impl IndexShift {
+    pub(crate) fn new(index: LogIndex, timestamp_index: LogIndex) -> Self {
+        IndexShift(index.0 as isize - timestamp_index.0 as isize)
+    }
+}
 
 impl Add<&IndexShift> for &LogIndex {
     type Output = LogIndex;
 
     fn add(self, other: &IndexShift) -> LogIndex {
-        LogIndex(self.0 + other.0)
+        LogIndex((self.0 as isize).wrapping_add(other.0) as usize)
     }
 }
 
@@ -120,7 +167,7 @@ impl Sub<&IndexShift> for &LogIndex {
     type Output = LogIndex;
 
     fn sub(self, other: &IndexShift) -> LogIndex {
-        LogIndex(self.0 - other.0)
+        LogIndex((self.0 as isize).wrapping_sub(other.0) as usize)
     }
 }
 
@@ -1,10 +1,20 @@
-use crate::index::{IndexShift, RelativeNextIndex};
-use crate::offsetmap::Offset;
-use crate::{Author, Change, Chronofold, LogIndex, Timestamp};
+use crate::{
+    Author, Change, Chronofold, IntoLocalValue, LogIndex, Op, OpPayload, Storage, Timestamp,
+};
 
-impl<A: Author, T> Chronofold<A, T> {
+impl<A: Author, T, S: Storage<A, T>> Chronofold<A, T, S> {
     pub(crate) fn next_log_index(&self) -> LogIndex {
-        LogIndex(self.log.len())
+        LogIndex(self.storage.len())
+    }
+
+    /// Returns the log index to use in the timestamp of the next local change.
+    ///
+    /// This is the next log index, unless the chronofold was compacted. In
+    /// that case the log is shorter than the logs of other authors and we
+    /// have to make sure not to reuse timestamps we've seen before.
+    pub(crate) fn next_timestamp_index(&self) -> LogIndex {
+        let known = self.version.iter().map(|t| t.0 .0 + 1).max().unwrap_or(0);
+        LogIndex(usize::max(self.storage.len(), known))
     }
 
     pub(crate) fn find_predecessor(
@@ -22,10 +32,10 @@ impl<A: Author, T> Chronofold<A, T> {
                 unreachable!()
             }
             (Some(reference), _change) => {
-                if let Some((_, idx, _)) = self
+                if let Some(idx) = self
                     .iter_log_indices_causal_range(reference..)
-                    .filter(|(_, i, _)| {
-                        self.references.get(i) == Some(reference)
+                    .filter(|i| {
+                        self.storage.reference(*i) == Some(reference)
                             && self.timestamp(*i).unwrap() > id
                     })
                     .last()
@@ -43,6 +53,50 @@ impl<A: Author, T> Chronofold<A, T> {
         }
     }
 
+    /// Returns the op describing the change at `index`, converting its value
+    /// with `f`.
+    pub(crate) fn op_at<V, F>(&self, index: LogIndex, f: F) -> Option<Op<A, V>>
+    where
+        F: FnOnce(&T) -> V,
+    {
+        let id = self.timestamp(index)?;
+        let reference = self.storage.reference(index).map(|r| {
+            self.timestamp(r)
+                .expect("references of already applied ops have to exist")
+        });
+        let payload = match &*self.storage.get(index)? {
+            (Change::Root, _) => OpPayload::Root,
+            (Change::Insert(v), _) => OpPayload::Insert(reference, f(v)),
+            (Change::Delete, _) => {
+                OpPayload::Delete(reference.expect("deletes must have a reference"))
+            }
+        };
+        Some(Op::new(id, payload))
+    }
+
+    /// Returns `true` if `op` describes the change at `index`.
+    pub(crate) fn describes<V>(&self, index: LogIndex, op: &Op<A, V>) -> bool
+    where
+        V: IntoLocalValue<A, T, S> + Clone,
+        T: PartialEq,
+    {
+        let reference = self
+            .storage
+            .reference(index)
+            .and_then(|r| self.timestamp(r));
+        if reference.as_ref() != op.payload.reference() {
+            return false;
+        }
+        match (self.storage.get(index).as_deref(), &op.payload) {
+            (Some((Change::Root, _)), OpPayload::Root)
+            | (Some((Change::Delete, _)), OpPayload::Delete(_)) => true,
+            (Some((Change::Insert(existing), _)), OpPayload::Insert(_, value)) => {
+                *existing == value.clone().into_local_value(self)
+            }
+            _ => false,
+        }
+    }
+
     pub(crate) fn apply_change(
         &mut self,
         id: Timestamp<A>,
@@ -54,11 +108,11 @@ impl<A: Author, T> Chronofold<A, T> {
 
         // Set the predecessors next index to our new change's index while
         // keeping it's previous next index for ourselves.
-        let new_index = LogIndex(self.log.len());
+        let new_index = self.next_log_index();
         let next_index;
         if let Some(idx) = predecessor {
-            next_index = self.next_indices.get(&idx);
-            self.next_indices.set(idx, Some(new_index));
+            next_index = self.storage.next_index(idx);
+            self.storage.set_next_index(idx, Some(new_index));
         } else {
             // Inserting another root will result in two disjunct subsequences.
             next_index = None;
@@ -69,12 +123,7 @@ impl<A: Author, T> Chronofold<A, T> {
         }
 
         // Append to the chronofold's log and secondary logs.
-        self.log.push((change, None));
-        self.next_indices.set(new_index, next_index);
-        self.authors.set(new_index, id.1);
-        self.index_shifts
-            .set(new_index, IndexShift(new_index.0 - (id.0).0));
-        self.references.set(new_index, reference);
+        self.storage.push(change, id, reference, next_index);
 
         // Increment version.
         self.version.inc(&id);
@@ -87,7 +136,7 @@ impl<A: Author, T> Chronofold<A, T> {
     /// For local changes the following optimizations can be applied:
     /// - id equals (log index, author)
     /// - predecessor always equals reference (no preemptive siblings)
-    /// - next index has to be set only for the first and the last change
+    /// - next index has to be changed only for the first and the last change
     pub(crate) fn apply_local_changes<I>(
         &mut self,
         author: A,
@@ -97,62 +146,68 @@ impl<A: Author, T> Chronofold<A, T> {
     where
         I: IntoIterator<Item = Change<T>>,
     {
-        let mut last_id = None;
+        let mut last_id: Option<Timestamp<A>> = None;
         let mut last_next_index = None;
 
         let mut predecessor = reference;
 
         let mut changes = changes.into_iter();
         if let Some(first_change) = changes.next() {
-            let new_index = LogIndex(self.log.len());
-            let id = Timestamp(new_index, author);
+            assert!(
+                !self.storage.is_empty(),
+                "chronofold has no root, see `Session::create_root`"
+            );
+            let new_index = self.next_log_index();
+            let id = Timestamp(self.next_timestamp_index(), author);
             last_id = Some(id);
 
             // Set the predecessors next index to our new change's index while
             // keeping it's previous next index for ourselves.
-            last_next_index = Some(self.next_indices.get(&predecessor));
-            self.next_indices.set(predecessor, Some(new_index));
+            last_next_index = Some(self.storage.next_index(predecessor));
+            self.storage.set_next_index(predecessor, Some(new_index));
 
             if let Change::Delete = &first_change {
                 self.mark_as_deleted(predecessor, new_index);
             }
 
-            self.log.push((first_change, None));
-            self.authors.set(new_index, author);
-            self.index_shifts.set(new_index, IndexShift(0));
-            self.references.set(new_index, Some(predecessor));
+            let next_index = LogIndex(new_index.0 + 1);
+            self.storage
+                .push(first_change, id, Some(predecessor), Some(next_index));
 
             predecessor = new_index;
         }
 
         for change in changes {
-            let new_index = RelativeNextIndex::default().add(&predecessor);
-            let id = Timestamp(new_index, author);
-            last_id = Some(id);
+            let new_index = LogIndex(predecessor.0 + 1);
+            let id = last_id.map(|id| Timestamp(LogIndex(id.0 .0 + 1), author));
+            last_id = id;
 
             if let Change::Delete = &change {
                 self.mark_as_deleted(predecessor, new_index);
             }
 
             // Append to the chronofold's log and secondary logs.
-            self.log.push((change, None));
+            let next_index = LogIndex(new_index.0 + 1);
+            self.storage
+                .push(change, id.unwrap(), Some(predecessor), Some(next_index));
 
             predecessor = new_index;
         }
 
         if let (Some(id), Some(next_index)) = (last_id, last_next_index) {
-            self.next_indices.set(id.0, next_index);
+            self.storage.set_next_index(predecessor, next_index);
             self.version.inc(&id);
-            Some(id.0)
+            Some(predecessor)
         } else {
             None
         }
     }
 
     fn mark_as_deleted(&mut self, index: LogIndex, deletion: LogIndex) {
-        self.log[index.0].1 = Some(match self.log[index.0].1 {
+        let earliest = match self.storage.get(index).unwrap().1 {
             None => deletion,
             Some(other_deletion) => LogIndex(usize::min(deletion.0, other_deletion.0)),
-        })
+        };
+        self.storage.set_earliest_deletion(index, Some(earliest));
     }
 }
@@ -0,0 +1,224 @@
+use std::collections::BTreeSet;
+
+use crate::{Author, Change, Chronofold, InvariantError, LogIndex, Storage};
+
+impl<A: Author, T, S: Storage<A, T>> Chronofold<A, T, S> {
+    /// Checks the consistency of the chronofold's internal data structures.
+    ///
+    /// Chronofolds created by this crate always satisfy these invariants, so
+    /// this is only useful for data from untrusted sources. Deserializing a
+    /// chronofold performs this check as well.
+    pub fn check_invariants(&self) -> Result<(), InvariantError> {
+        use InvariantError::*;
+
+        let storage = &self.storage;
+        storage.check_invariants()?;
+        let len = storage.len();
+        let is_root = |idx| matches!(storage.get(idx).as_deref(), Some((Change::Root, _)));
+        if len > 0 && !is_root(self.root) {
+            return Err(InvalidRoot(self.root));
+        }
+
+        // Timestamps have to be unique and must not overflow when we compute
+        // the next timestamp.
+        let mut timestamps = BTreeSet::new();
+        let mut version = self.compacted.clone();
+        for idx in (0..len).map(LogIndex) {
+            match self.timestamp(idx) {
+                Some(t) if t.0 .0 <= isize::MAX as usize && timestamps.insert(t) => {
+                    version.inc(&t);
+                }
+                _ => return Err(InvalidTimestamp(idx)),
+            }
+        }
+        let version_too_large = self.version.iter().any(|t| t.0 .0 > isize::MAX as usize);
+        if version != self.version || version_too_large {
+            return Err(InvalidVersion);
+        }
+
+        // Every entry is part of exactly one linked list, which starts with
+        // an entry without predecessor. Only these may be roots.
+        let mut predecessors: Vec<Option<LogIndex>> = vec![None; len];
+        for idx in (0..len).map(LogIndex) {
+            if let Some(next) = storage.next_index(idx) {
+                if next.0 >= len || is_root(next) || predecessors[next.0].is_some() {
+                    return Err(InvalidNextIndex(idx));
+                }
+                predecessors[next.0] = Some(idx);
+            }
+        }
+        // The list an entry belongs to and its position within.
+        let mut positions: Vec<Option<(LogIndex, usize)>> = vec![None; len];
+        for head in (0..len)
+            .map(LogIndex)
+            .filter(|i| predecessors[i.0].is_none())
+        {
+            let mut current = Some(head);
+            let mut position = 0;
+            while let Some(idx) = current {
+                positions[idx.0] = Some((head, position));
+                current = storage.next_index(idx);
+                position += 1;
+            }
+        }
+        if let Some(idx) = positions.iter().position(Option::is_none) {
+            // Entries forming a cycle can't be reached from any head.
+            return Err(InvalidNextIndex(LogIndex(idx)));
+        }
+
+        // There is only one root and only inserts may be deleted, as `apply`
+        // rejects everything else.
+        if let Some(idx) = (0..len)
+            .map(LogIndex)
+            .find(|idx| *idx != self.root && is_root(*idx))
+        {
+            return Err(InvalidRoot(idx));
+        }
+
+        // References have to precede their entries in log and causal order.
+        for i in 0..len {
+            let idx = LogIndex(i);
+            let valid = match (&storage.get(idx).unwrap().0, storage.reference(idx)) {
+                (Change::Root, None) => true,
+                (Change::Root, Some(_)) | (_, None) => false,
+                (Change::Delete, Some(reference))
+                    if !matches!(
+                        storage.get(reference).as_deref(),
+                        Some((Change::Insert(_), _))
+                    ) =>
+                {
+                    false
+                }
+                (_, Some(reference)) => {
+                    let (list, position) = positions[i].unwrap();
+                    reference < idx
+                        && positions[reference.0].is_some_and(|(l, p)| l == list && p < position)
+                }
+            };
+            if !valid {
+                return Err(InvalidReference(idx));
+            }
+        }
+
+        // Earliest deletions have to match the deletes referencing an entry.
+        let mut earliest_deletions: Vec<Option<LogIndex>> = vec![None; len];
+        for idx in (0..len).rev().map(LogIndex) {
+            if let Change::Delete = storage.get(idx).unwrap().0 {
+                let reference = storage.reference(idx).unwrap();
+                earliest_deletions[reference.0] = Some(idx);
+            }
+        }
+        for idx in (0..len).map(LogIndex) {
+            if storage.get(idx).unwrap().1 != earliest_deletions[idx.0] {
+                return Err(InvalidDeletion(idx));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use std::marker::PhantomData;
+
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::index::{IndexShift, RelativeNextIndex, RelativeReference};
+    use crate::offsetmap::OffsetMap;
+    use crate::rangemap::RangeFromMap;
+    use crate::{Author, Change, Chronofold, EarliestDeletion, LogIndex, MemoryStorage, Version};
+
+    /// The fields of a chronofold kept in memory, as they are serialized.
+    #[derive(Serialize)]
+    #[serde(
+        rename = "Chronofold",
+        bound(serialize = "A: Author + Serialize, T: Serialize")
+    )]
+    struct Fields<'a, A, T> {
+        log: &'a Vec<(Change<T>, EarliestDeletion)>,
+        root: LogIndex,
+        version: &'a Version<A>,
+        #[serde(skip_serializing_if = "is_empty")]
+        compacted: &'a Version<A>,
+        next_indices: &'a OffsetMap<LogIndex, RelativeNextIndex>,
+        references: &'a OffsetMap<LogIndex, RelativeReference>,
+        authors: &'a RangeFromMap<LogIndex, A>,
+        index_shifts: &'a RangeFromMap<LogIndex, IndexShift>,
+    }
+
+    fn is_empty<A: Author>(version: &&Version<A>) -> bool {
+        version.is_empty()
+    }
+
+    impl<A, T> Serialize for Chronofold<A, T>
+    where
+        A: Author + Serialize,
+        T: Serialize,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let storage = &self.storage;
+            Fields {
+                log: &storage.log,
+                root: self.root,
+                version: &self.version,
+                compacted: &self.compacted,
+                next_indices: &storage.next_indices,
+                references: &storage.references,
+                authors: &storage.authors,
+                index_shifts: &storage.index_shifts,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    /// The fields of a chronofold, which may violate its invariants.
+    #[derive(Deserialize)]
+    #[serde(
+        rename = "Chronofold",
+        bound(deserialize = "A: Author + Deserialize<'de>, T: Deserialize<'de>")
+    )]
+    struct Unchecked<A, T> {
+        log: Vec<(Change<T>, EarliestDeletion)>,
+        root: LogIndex,
+        version: Version<A>,
+        #[serde(default)]
+        compacted: Version<A>,
+        next_indices: OffsetMap<LogIndex, RelativeNextIndex>,
+        references: OffsetMap<LogIndex, RelativeReference>,
+        authors: RangeFromMap<LogIndex, A>,
+        index_shifts: RangeFromMap<LogIndex, IndexShift>,
+    }
+
+    impl<'de, A, T> Deserialize<'de> for Chronofold<A, T>
+    where
+        A: Author + Deserialize<'de>,
+        T: Deserialize<'de>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let unchecked = Unchecked::deserialize(deserializer)?;
+            let cfold = Chronofold {
+                storage: MemoryStorage {
+                    log: unchecked.log,
+                    next_indices: unchecked.next_indices,
+                    references: unchecked.references,
+                    authors: unchecked.authors,
+                    index_shifts: unchecked.index_shifts,
+                },
+                root: unchecked.root,
+                version: unchecked.version,
+                compacted: unchecked.compacted,
+                _values: PhantomData,
+            };
+            cfold.check_invariants().map_err(D::Error::custom)?;
+            Ok(cfold)
+        }
+    }
+}
@@ -4,15 +4,16 @@ use std::matches;
 use std::ops::{Bound, Range, RangeBounds};
 
 use crate::{
-    Author, Change, Chronofold, EarliestDeletion, FromLocalValue, LogIndex, Op, OpPayload,
+    Author, Change, Chronofold, EarliestDeletion, FromLocalValue, LendingStorage, LogIndex,
+    MemoryStorage, Op, OpPayload, Storage,
 };
 
-impl<A: Author, T> Chronofold<A, T> {
+impl<A: Author, T, S: Storage<A, T>> Chronofold<A, T, S> {
     /// Returns an iterator over the log indices in causal order.
     ///
     /// TODO: The name is a bit unwieldy. I'm reluctant to add it to the public
     /// API before giving it more thought.
-    pub(crate) fn iter_log_indices_causal_range<R>(&self, range: R) -> CausalIter<'_, A, T>
+    pub(crate) fn iter_log_indices_causal_range<R>(&self, range: R) -> CausalIter<'_, A, T, S>
     where
         R: RangeBounds<LogIndex>,
     {
@@ -21,8 +22,10 @@ impl<A: Author, T> Chronofold<A, T> {
             Bound::Included(idx) => Some(*idx),
             Bound::Excluded(idx) => self.index_after(*idx),
         };
-        if let Some((Some(Change::Root), idx)) = current.map(|idx| (self.get(idx), idx)) {
-            current = self.index_after(idx);
+        if let Some(idx) = current {
+            if matches!(self.storage.get(idx).as_deref(), Some((Change::Root, _))) {
+                current = self.index_after(idx);
+            }
         }
         let first_excluded = match range.end_bound() {
             Bound::Unbounded => None,
@@ -42,8 +45,8 @@ impl<A: Author, T> Chronofold<A, T> {
     pub(crate) fn iter_subtree(&self, root: LogIndex) -> impl Iterator<Item = LogIndex> + '_ {
         let mut subtree: HashSet<LogIndex> = HashSet::new();
         self.iter_log_indices_causal_range(root..)
-            .filter_map(move |(_, idx, _)| {
-                if idx == root || subtree.contains(&self.references.get(&idx)?) {
+            .filter_map(move |idx| {
+                if idx == root || subtree.contains(&self.storage.reference(idx)?) {
                     subtree.insert(idx);
                     Some(idx)
                 } else {
@@ -52,13 +55,78 @@ impl<A: Author, T> Chronofold<A, T> {
             })
     }
 
+    /// Returns an iterator over the log indices of elements in causal order.
+    pub(crate) fn iter_element_indices<R>(&self, range: R) -> impl Iterator<Item = LogIndex> + '_
+    where
+        R: RangeBounds<LogIndex>,
+    {
+        self.iter_log_indices_causal_range(range)
+            .filter(move |idx| self.is_element(*idx))
+    }
+
+    /// Returns `true` if the change at `index` is an insert which is not
+    /// deleted.
+    pub(crate) fn is_element(&self, index: LogIndex) -> bool {
+        matches!(
+            self.storage.get(index).as_deref(),
+            Some((Change::Insert(_), None))
+        )
+    }
+
+    /// Returns an iterator over ops with copies of their values in log order.
+    ///
+    /// Unlike `iter_ops`, this works with any storage.
+    pub fn iter_cloned_ops<R>(&self, range: R) -> impl Iterator<Item = Op<A, T>> + '_
+    where
+        R: RangeBounds<LogIndex>,
+        T: Clone,
+    {
+        self.log_range(range)
+            .filter_map(move |i| self.op_at(LogIndex(i), T::clone))
+    }
+
+    /// Returns the log indices in `range` as a range of `usize`.
+    fn log_range<R>(&self, range: R) -> Range<usize>
+    where
+        R: RangeBounds<LogIndex>,
+    {
+        let oob = LogIndex(self.storage.len());
+        let start = match range.start_bound() {
+            Bound::Unbounded => LogIndex(0),
+            Bound::Included(idx) => *idx,
+            Bound::Excluded(idx) => self.index_after(*idx).unwrap_or(oob),
+        }
+        .0;
+        let end = match range.end_bound() {
+            Bound::Unbounded => oob,
+            Bound::Included(idx) => self.index_after(*idx).unwrap_or(oob),
+            Bound::Excluded(idx) => *idx,
+        }
+        .0;
+        start..end
+    }
+
+    /// Returns a copy of the element at `index`, if the change there is an
+    /// insert which is not deleted.
+    pub(crate) fn cloned_element(&self, index: LogIndex) -> Option<T>
+    where
+        T: Clone,
+    {
+        match &*self.storage.get(index)? {
+            (Change::Insert(value), None) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl<A: Author, T, S: LendingStorage<A, T>> Chronofold<A, T, S> {
     /// Returns an iterator over elements and their log indices in causal order.
-    pub fn iter(&self) -> Iter<A, T> {
+    pub fn iter(&self) -> Iter<'_, A, T, S> {
         self.iter_range(..)
     }
 
     /// Returns an iterator over elements and their log indices in causal order.
-    pub fn iter_range<R>(&self, range: R) -> Iter<A, T>
+    pub fn iter_range<R>(&self, range: R) -> Iter<'_, A, T, S>
     where
         R: RangeBounds<LogIndex>,
     {
@@ -73,51 +141,39 @@ impl<A: Author, T> Chronofold<A, T> {
 
     /// Returns an iterator over changes in log order.
     pub fn iter_changes(&self) -> impl Iterator<Item = &(Change<T>, EarliestDeletion)> {
-        self.log.iter()
+        (0..self.storage.len()).filter_map(|i| self.storage.get_ref(LogIndex(i)))
     }
 
     /// Returns an iterator over ops in log order.
-    pub fn iter_ops<'a, R, V>(&'a self, range: R) -> Ops<'a, A, T, V>
+    pub fn iter_ops<'a, R, V>(&'a self, range: R) -> Ops<'a, A, T, V, S>
     where
         R: RangeBounds<LogIndex> + 'a,
-        V: FromLocalValue<'a, A, T>,
+        V: FromLocalValue<'a, A, T, S>,
     {
-        let oob = LogIndex(self.log.len());
-        let start = match range.start_bound() {
-            Bound::Unbounded => LogIndex(0),
-            Bound::Included(idx) => *idx,
-            Bound::Excluded(idx) => self.index_after(*idx).unwrap_or(oob),
-        }
-        .0;
-        let end = match range.end_bound() {
-            Bound::Unbounded => oob,
-            Bound::Included(idx) => self.index_after(*idx).unwrap_or(oob),
-            Bound::Excluded(idx) => *idx,
-        }
-        .0;
         Ops {
             cfold: self,
-            idx_iter: start..end,
+            idx_iter: self.log_range(range),
             _op_value: PhantomData,
         }
     }
 }
 
-pub(crate) struct CausalIter<'a, A, T> {
-    cfold: &'a Chronofold<A, T>,
+pub(crate) struct CausalIter<'a, A, T, S = MemoryStorage<A, T>> {
+    cfold: &'a Chronofold<A, T, S>,
     current: Option<LogIndex>,
     first_excluded: Option<LogIndex>,
 }
 
-impl<'a, A: Author, T> Iterator for CausalIter<'a, A, T> {
-    type Item = (&'a Change<T>, LogIndex, EarliestDeletion);
+impl<A: Author, T, S: Storage<A, T>> Iterator for CausalIter<'_, A, T, S> {
+    type Item = LogIndex;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.current.take() {
-            Some(current) if Some(current) != self.first_excluded => {
+            Some(current)
+                if Some(current) != self.first_excluded && current.0 < self.cfold.storage.len() =>
+            {
                 self.current = self.cfold.index_after(current);
-                let (change, deleted) = &self.cfold.log.get(current.0)?;
-                Some((change, current, *deleted))
+                Some(current)
             }
             _ => None,
         }
@@ -128,27 +184,19 @@ impl<'a, A: Author, T> Iterator for CausalIter<'a, A, T> {
 ///
 /// This struct is created by the `iter` and `iter_range` methods on
 /// `Chronofold`. See its documentation for more.
-pub struct Iter<'a, A, T> {
-    causal_iter: CausalIter<'a, A, T>,
+pub struct Iter<'a, A, T, S = MemoryStorage<A, T>> {
+    causal_iter: CausalIter<'a, A, T, S>,
 }
 
-impl<'a, A: Author, T> Iterator for Iter<'a, A, T> {
+impl<'a, A: Author, T, S: LendingStorage<A, T>> Iterator for Iter<'a, A, T, S> {
     type Item = (&'a T, LogIndex);
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            let next = skip_while(&mut self.causal_iter, |(c, _, deleted)| {
-                matches!(c, Change::Delete) || deleted.is_some()
-            });
-            match next {
-                None => {
-                    return None;
-                }
-                Some((Change::Insert(v), idx, _)) => {
-                    return Some((v, idx));
-                }
-                _ => unreachable!(),
-            }
+        let cfold = self.causal_iter.cfold;
+        let idx = skip_while(&mut self.causal_iter, |idx| !cfold.is_element(*idx))?;
+        match cfold.storage.get_ref(idx) {
+            Some((Change::Insert(v), _)) => Some((v, idx)),
+            _ => unreachable!(),
         }
     }
 }
@@ -157,16 +205,17 @@ impl<'a, A: Author, T> Iterator for Iter<'a, A, T> {
 ///
 /// This struct is created by the `iter_ops` method on `Chronofold`. See its
 /// documentation for more.
-pub struct Ops<'a, A, T, V> {
-    cfold: &'a Chronofold<A, T>,
+pub struct Ops<'a, A, T, V, S = MemoryStorage<A, T>> {
+    cfold: &'a Chronofold<A, T, S>,
     idx_iter: Range<usize>,
     _op_value: PhantomData<V>,
 }
 
-impl<'a, A, T, V> Iterator for Ops<'a, A, T, V>
+impl<'a, A, T, V, S> Iterator for Ops<'a, A, T, V, S>
 where
     A: Author,
-    V: FromLocalValue<'a, A, T>,
+    S: LendingStorage<A, T>,
+    V: FromLocalValue<'a, A, T, S>,
 {
     type Item = Op<A, V>;
 
@@ -176,12 +225,12 @@ where
             .cfold
             .timestamp(idx)
             .expect("timestamps of already applied ops have to exist");
-        let reference = self.cfold.references.get(&idx).map(|r| {
+        let reference = self.cfold.storage.reference(idx).map(|r| {
             self.cfold
                 .timestamp(r)
                 .expect("references of already applied ops have to exist")
         });
-        let payload = match &self.cfold.log[idx.0].0 {
+        let payload = match &self.cfold[idx] {
             Change::Root => OpPayload::Root,
             Change::Insert(v) => OpPayload::Insert(reference, V::from_local_value(v, self.cfold)),
             Change::Delete => OpPayload::Delete(reference.expect("deletes must have a reference")),
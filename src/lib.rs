@@ -70,32 +70,73 @@
 // As we only have a handful of public items, we've decided to re-export
 // everything in the crate root and keep our internal module structure
 // private. This keeps things simple for our users and gives us more
-// flexibility in restructuring the crate.
+// flexibility in restructuring the crate. Test utilities are the exception,
+// as they shouldn't clutter the crate root.
+#[cfg(feature = "rkyv")]
+mod archive;
+#[cfg(feature = "futures")]
+mod asynchronous;
+mod buffer;
 mod change;
+mod compaction;
 mod debug;
+mod digest;
 mod distributed;
 mod error;
 mod fmt;
+mod hub;
 mod index;
 mod internal;
+mod invariants;
 mod iter;
 mod offsetmap;
+#[cfg(feature = "oplog")]
+mod oplog;
+mod outcome;
+mod patch;
+mod persistent;
 mod rangemap;
+mod registry;
+mod relay;
 mod session;
+mod shared;
+mod snapshot;
+mod storage;
+#[cfg(feature = "sync")]
+mod sync;
+mod syncdigest;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod version;
 
+#[cfg(feature = "rkyv")]
+pub use crate::archive::*;
+#[cfg(feature = "futures")]
+pub use crate::asynchronous::*;
+pub use crate::buffer::*;
 pub use crate::change::*;
+pub use crate::digest::*;
 pub use crate::distributed::*;
 pub use crate::error::*;
-pub use crate::fmt::*;
+pub use crate::hub::*;
 pub use crate::index::*;
 pub use crate::iter::*;
+#[cfg(feature = "oplog")]
+pub use crate::oplog::*;
+pub use crate::outcome::*;
+pub use crate::persistent::*;
+pub use crate::registry::*;
+pub use crate::relay::*;
 pub use crate::session::*;
+pub use crate::shared::*;
+pub use crate::snapshot::*;
+pub use crate::storage::*;
+#[cfg(feature = "sync")]
+pub use crate::sync::*;
+pub use crate::syncdigest::*;
 pub use crate::version::*;
 
-use crate::index::{IndexShift, RelativeNextIndex, RelativeReference};
-use crate::offsetmap::OffsetMap;
-use crate::rangemap::RangeFromMap;
+use std::marker::PhantomData;
 
 #[cfg(feature = "serde")]
 #[macro_use]
@@ -128,26 +169,30 @@ extern crate serde;
 /// out-of-bound indexes cause panics, and you can use `get` to check whether
 /// the index exists.
 ///
+/// # Storage
+///
+/// The log is kept in a [`Storage`], which is [`MemoryStorage`] unless
+/// specified otherwise. Use `new_in` or `empty_in` to construct a chronofold
+/// with a different storage.
+///
+/// Editing, applying ops, formatting, digests and `check_invariants` work
+/// with any storage, as do `iter_cloned_ops` and `iter_newer_cloned_ops`.
+/// Methods lending out values, like `get`, indexing, `iter` and `iter_ops`,
+/// require a [`LendingStorage`]. The following are only
+/// available for `MemoryStorage`: `compact`, `from_snapshot`, `to_archive`
+/// and serialization with serde.
+///
 /// [`Vec`]: https://doc.rust-lang.org/std/vec/struct.Vec.html
 /// [`Index`]: https://doc.rust-lang.org/std/ops/trait.Index.html
 #[derive(PartialEq, Eq, Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct Chronofold<A, T> {
-    log: Vec<(Change<T>, EarliestDeletion)>,
+pub struct Chronofold<A, T, S = MemoryStorage<A, T>> {
+    storage: S,
     root: LogIndex,
-    #[cfg_attr(
-        feature = "serde",
-        serde(bound(
-            serialize = "Version<A>: serde::Serialize",
-            deserialize = "Version<A>: serde::Deserialize<'de>"
-        ))
-    )]
     version: Version<A>,
-
-    next_indices: OffsetMap<LogIndex, RelativeNextIndex>,
-    references: OffsetMap<LogIndex, RelativeReference>,
-    authors: RangeFromMap<LogIndex, A>,
-    index_shifts: RangeFromMap<LogIndex, IndexShift>,
+    /// Changes covered by this version, that are missing from the log, were
+    /// removed by `compact`.
+    compacted: Version<A>,
+    _values: PhantomData<T>,
 }
 
 pub type EarliestDeletion = Option<LogIndex>;
@@ -155,40 +200,42 @@ pub type EarliestDeletion = Option<LogIndex>;
 impl<A: Author, T> Chronofold<A, T> {
     /// Constructs a new, empty chronofold.
     pub fn new(author: A) -> Self {
-        let root_idx = LogIndex(0);
-        let mut version = Version::default();
-        version.inc(&Timestamp(root_idx, author));
-        let mut next_indices = OffsetMap::default();
-        next_indices.set(root_idx, None);
-        let mut authors = RangeFromMap::default();
-        authors.set(root_idx, author);
-        let mut index_shifts = RangeFromMap::default();
-        index_shifts.set(root_idx, IndexShift(0));
-        let mut references = OffsetMap::default();
-        references.set(root_idx, None);
-        Self {
-            log: vec![(Change::Root, None)],
-            root: LogIndex(0),
-            version,
-            next_indices,
-            authors,
-            index_shifts,
-            references,
-        }
+        Self::new_in(author, MemoryStorage::new())
     }
 
     pub fn empty() -> Self {
+        Self::empty_in(MemoryStorage::new())
+    }
+}
+
+impl<A: Author, T, S: Storage<A, T>> Chronofold<A, T, S> {
+    /// Constructs a new, empty chronofold backed by `storage`.
+    ///
+    /// `storage` has to be empty.
+    pub fn new_in(author: A, storage: S) -> Self {
+        let mut cfold = Self::empty_in(storage);
+        cfold.apply_change(Timestamp(LogIndex(0), author), None, Change::Root);
+        cfold
+    }
+
+    /// Constructs a chronofold without a root backed by `storage`.
+    ///
+    /// `storage` has to be empty.
+    pub fn empty_in(storage: S) -> Self {
         Self {
-            log: vec![],
+            storage,
             root: LogIndex(0),
             version: Version::default(),
-            next_indices: OffsetMap::default(),
-            authors: RangeFromMap::default(),
-            index_shifts: RangeFromMap::default(),
-            references: OffsetMap::default(),
+            compacted: Version::default(),
+            _values: PhantomData,
         }
     }
 
+    /// Returns the chronofold's storage.
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
     /// Returns `true` if the chronofold contains no elements.
     pub fn is_empty(&self) -> bool {
         self.len() == 0
@@ -196,73 +243,75 @@ impl<A: Author, T> Chronofold<A, T> {
 
     /// Returns the number of elements in the chronofold.
     pub fn len(&self) -> usize {
-        self.iter().count()
-    }
-
-    /// Returns a reference to a change in the chronofold's log.
-    ///
-    /// If `index` is out of bounds, `None` is returned.
-    pub fn get(&self, index: LogIndex) -> Option<&Change<T>> {
-        self.log.get(index.0).map(|e| &e.0)
+        self.iter_element_indices(..).count()
     }
 
     /// Creates an editing session for a single author.
-    pub fn session(&mut self, author: A) -> Session<'_, A, T> {
+    pub fn session(&mut self, author: A) -> Session<'_, A, T, S> {
         Session::new(author, self)
     }
 
     pub fn log_index(&self, timestamp: &Timestamp<A>) -> Option<LogIndex> {
-        for i in (timestamp.0).0..self.log.len() {
-            if self.timestamp(LogIndex(i)).unwrap() == *timestamp {
-                return Some(LogIndex(i));
-            }
+        if !self.version.contains(timestamp) {
+            return None;
         }
-        None
+        // Changes are usually stored at log indices greater or equal than the
+        // ones in their timestamps. Only compaction moves them to smaller log
+        // indices.
+        let len = self.storage.len();
+        let start = usize::min((timestamp.0).0, len);
+        (start..len)
+            .chain((0..start).rev())
+            .map(LogIndex)
+            .find(|idx| self.timestamp(*idx).unwrap() == *timestamp)
     }
 
     pub fn timestamp(&self, index: LogIndex) -> Option<Timestamp<A>> {
-        if let (Some(shift), Some(author)) =
-            (self.index_shifts.get(&index), self.authors.get(&index))
-        {
-            Some(Timestamp(&index - shift, *author))
-        } else {
-            None
-        }
+        self.storage.timestamp(index)
     }
 
-    /// Applies an op to the chronofold.
-    pub fn apply<V>(&mut self, op: Op<A, V>) -> Result<(), ChronofoldError<A, V>>
+    /// Applies an op to the chronofold and returns its effect.
+    ///
+    /// Applying an op with a timestamp that was applied before results in
+    /// `ChronofoldError::ExistingTimestamp`, even if the ops differ, i.e.
+    /// equivocation is not detected. Use `apply_checked` for that.
+    pub fn apply<V>(&mut self, op: Op<A, V>) -> Result<Effect, ChronofoldError<A, V>>
     where
-        V: IntoLocalValue<A, T>,
+        V: IntoLocalValue<A, T, S>,
     {
-        // Check if an op with the same id was applied already.
+        // Check if an op with the same id was applied already. Ops that were
+        // applied and later removed by `compact` count as well.
         // TODO: Consider adding an `apply_unchecked` variant to skip this
         // check.
-        if self.log_index(&op.id).is_some() {
+        if self.compacted.contains(&op.id) || self.log_index(&op.id).is_some() {
             return Err(ChronofoldError::ExistingTimestamp(op));
         }
 
-        // We rely on indices in timestamps being greater or equal than their
-        // indices in every local log. This means we cannot apply an op not
-        // matching this constraint, even if we know the reference.
-        if op.id.0 .0 > self.log.len() {
+        // The index in an op's timestamp is the number of changes its author
+        // knew of. If that's more than we know of, we have not seen all the
+        // changes the op might depend on.
+        if op.id.0 > self.next_timestamp_index() {
             return Err(ChronofoldError::FutureTimestamp(op));
         }
 
         use OpPayload::*;
         match op.payload {
-            Root => {
-                self.apply_change(op.id, None, Change::Root);
-                Ok(())
-            }
+            Root if !self.storage.is_empty() => Err(ChronofoldError::MalformedRoot(op)),
+            Root => Ok(Effect {
+                index: self.apply_change(op.id, None, Change::Root),
+                changed: false,
+            }),
             Insert(Some(t), value) => match self.log_index(&t) {
                 Some(reference) => {
-                    self.apply_change(
+                    let index = self.apply_change(
                         op.id,
                         Some(reference),
                         Change::Insert(value.into_local_value(self)),
                     );
-                    Ok(())
+                    Ok(Effect {
+                        index,
+                        changed: true,
+                    })
                 }
                 None => Err(ChronofoldError::UnknownReference(Op::insert(
                     op.id,
@@ -270,18 +319,60 @@ impl<A: Author, T> Chronofold<A, T> {
                     value,
                 ))),
             },
-            Insert(None, value) => {
-                self.apply_change(op.id, None, Change::Insert(value.into_local_value(self)));
-                Ok(())
+            // Only roots start a sequence, all other changes have to be
+            // anchored.
+            Insert(None, _) => Err(ChronofoldError::InvalidReferenceKind(op)),
+            Delete(t) => {
+                let reference = match self.log_index(&t) {
+                    Some(reference) => reference,
+                    None => return Err(ChronofoldError::UnknownReference(op)),
+                };
+                let deleted = match self.storage.get(reference).as_deref() {
+                    Some((Change::Insert(_), deletion)) => deletion.is_some(),
+                    Some((Change::Root, _)) => return Err(ChronofoldError::DeleteOfRoot(op)),
+                    _ => return Err(ChronofoldError::InvalidReferenceKind(op)),
+                };
+                Ok(Effect {
+                    index: self.apply_change(op.id, Some(reference), Change::Delete),
+                    // Deleting an element that was deleted before has no
+                    // visible effect.
+                    changed: !deleted,
+                })
             }
-            Delete(t) => match self.log_index(&t) {
-                Some(reference) => {
-                    self.apply_change(op.id, Some(reference), Change::Delete);
-                    Ok(())
+        }
+    }
+
+    /// Applies an op like `apply`, but reports a different op with the
+    /// timestamp of one applied before as `ChronofoldError::Equivocation`,
+    /// along with the op applied before.
+    ///
+    /// Ops removed by `compact` can't be compared and still result in
+    /// `ChronofoldError::ExistingTimestamp`.
+    pub fn apply_checked<V>(&mut self, op: Op<A, V>) -> Result<Effect, ChronofoldError<A, V>>
+    where
+        V: IntoLocalValue<A, T, S> + Clone,
+        T: PartialEq + Clone + Into<V>,
+    {
+        if let Some(idx) = self.log_index(&op.id) {
+            return Err(if self.describes(idx, &op) {
+                ChronofoldError::ExistingTimestamp(op)
+            } else {
+                ChronofoldError::Equivocation {
+                    existing: self.op_at(idx, |v| v.clone().into()).unwrap(),
+                    received: op,
                 }
-                None => Err(ChronofoldError::UnknownReference(op)),
-            },
+            });
         }
+        self.apply(op)
+    }
+}
+
+impl<A: Author, T, S: LendingStorage<A, T>> Chronofold<A, T, S> {
+    /// Returns a reference to a change in the chronofold's log.
+    ///
+    /// If `index` is out of bounds, `None` is returned.
+    pub fn get(&self, index: LogIndex) -> Option<&Change<T>> {
+        self.storage.get_ref(index).map(|e| &e.0)
     }
 }
 
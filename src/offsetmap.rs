@@ -27,6 +27,11 @@ impl<K: Ord, O> OffsetMap<K, O> {
             map: BTreeMap::new(),
         }
     }
+
+    /// Returns the greatest key with a stored value.
+    pub fn last_key(&self) -> Option<&K> {
+        self.map.keys().next_back()
+    }
 }
 
 impl<K: Ord, O: Offset<K>> OffsetMap<K, O> {
@@ -38,6 +43,15 @@ impl<K: Ord, O: Offset<K>> OffsetMap<K, O> {
         }
     }
 
+    /// Returns an iterator over the stored entries, i.e. the ones not having
+    /// the default value.
+    #[cfg_attr(not(feature = "rkyv"), allow(dead_code))]
+    pub fn iter(&self) -> impl Iterator<Item = (&K, Option<K>)> {
+        self.map
+            .iter()
+            .map(|(key, offset)| (key, offset.as_ref().map(|o| o.add(key))))
+    }
+
     pub fn set(&mut self, key: K, value: Option<K>) {
         if let Some(value) = value {
             if O::default().add(&key) == value {
@@ -0,0 +1,252 @@
+//! Persistence of chronofolds as append-only logs of ops.
+
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::hash::Hasher;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::digest::StableHasher;
+use crate::{Author, Chronofold, ChronofoldError, Op};
+
+/// The size of a record's header: the payload's length and checksum, followed
+/// by the header's checksum.
+const HEADER_SIZE: usize = 20;
+
+/// An append-only file of ops.
+///
+/// Every op applied to a chronofold, local or remote, is appended as a
+/// record. A record consists of the length of its payload (`u32`), the
+/// payload's checksum (`u64`), the checksum of these two fields (`u64`, all
+/// little-endian) and the payload itself, the op serialized as JSON.
+///
+/// Opening the file replays all records. If the last record is cut off by
+/// the end of the file, e.g. because of a crash during `append`, it is
+/// truncated. This requires a valid header, unless the header itself is cut
+/// off. Trailing zeros, which some file systems leave behind after a crash,
+/// are truncated as well. Any other invalid record is reported as
+/// `OpLogError::Corrupt` and the file is left untouched.
+///
+/// To speed up loading, `snapshot` writes the whole chronofold to a separate
+/// file and empties the log.
+///
+/// ```rust
+/// # let dir = tempfile::tempdir().unwrap();
+/// use chronofold::{Chronofold, OpLog};
+///
+/// let path = dir.path().join("document.log");
+/// let (mut log, mut cfold) = OpLog::<u8, char>::open(&path).unwrap();
+/// let mut session = cfold.session(1);
+/// session.create_root().unwrap();
+/// session.extend("Hello!".chars());
+/// for op in session.iter_ops::<&char>() {
+///     log.append(&op).unwrap();
+/// }
+/// drop(session);
+///
+/// let (_, restored) = OpLog::<u8, char>::open(&path).unwrap();
+/// assert_eq!("Hello!", format!("{restored}"));
+/// ```
+#[derive(Debug)]
+pub struct OpLog<A, T> {
+    file: File,
+    path: PathBuf,
+    _marker: std::marker::PhantomData<(A, T)>,
+}
+
+impl<A, T> OpLog<A, T>
+where
+    A: Author + Serialize + DeserializeOwned,
+    T: Clone + PartialEq + Serialize + DeserializeOwned,
+{
+    /// Opens the log at `path`, creating it if it doesn't exist, and returns
+    /// it along with the chronofold restored from it.
+    ///
+    /// New logs start with an empty chronofold, i.e. the first op appended
+    /// has to be a root.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<(Self, Chronofold<A, T>), OpLogError<A, T>> {
+        let path = path.as_ref().to_owned();
+        let mut cfold = match fs::read(snapshot_path(&path)) {
+            Ok(json) => {
+                serde_json::from_slice(&json).map_err(|err| OpLogError::Decode(err.to_string()))?
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Chronofold::empty(),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)?;
+
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let payload = match read_record(&bytes[offset..]) {
+                Some(payload) => payload,
+                None if is_torn(&bytes[offset..]) => {
+                    file.set_len(offset as u64)?;
+                    break;
+                }
+                None => return Err(OpLogError::Corrupt(offset as u64)),
+            };
+            let op: Op<A, T> = serde_json::from_slice(payload)
+                .map_err(|err| OpLogError::Decode(err.to_string()))?;
+            // Ops of the last snapshot might be logged as well, if we crashed
+            // while taking it.
+            cfold.apply_idempotent(op).map_err(OpLogError::Apply)?;
+            offset += HEADER_SIZE + payload.len();
+        }
+        file.seek(SeekFrom::End(0))?;
+
+        let log = Self {
+            file,
+            path,
+            _marker: std::marker::PhantomData,
+        };
+        Ok((log, cfold))
+    }
+
+    /// Appends an op.
+    ///
+    /// The op is written to the operating system, use `sync` to make sure it
+    /// reached the disk.
+    pub fn append<V: Serialize>(&mut self, op: &Op<A, V>) -> Result<(), OpLogError<A, T>> {
+        let payload = serde_json::to_vec(op).map_err(|err| OpLogError::Encode(err.to_string()))?;
+        let length = u32::try_from(payload.len())
+            .map_err(|_| OpLogError::Encode("op too large".to_owned()))?;
+        let mut record = Vec::with_capacity(HEADER_SIZE + payload.len());
+        record.extend_from_slice(&length.to_le_bytes());
+        record.extend_from_slice(&checksum(&payload).to_le_bytes());
+        record.extend_from_slice(&checksum(&record).to_le_bytes());
+        record.extend_from_slice(&payload);
+        self.file.write_all(&record)?;
+        Ok(())
+    }
+
+    /// Flushes all appended ops to disk.
+    pub fn sync(&mut self) -> Result<(), OpLogError<A, T>> {
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    /// Writes `cfold` to the snapshot file and empties the log.
+    ///
+    /// `cfold` has to contain all ops appended so far.
+    pub fn snapshot(&mut self, cfold: &Chronofold<A, T>) -> Result<(), OpLogError<A, T>> {
+        let json = serde_json::to_vec(cfold).map_err(|err| OpLogError::Encode(err.to_string()))?;
+        let path = snapshot_path(&self.path);
+        let tmp_path = path.with_extension("snapshot.tmp");
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(&json)?;
+        tmp.sync_data()?;
+        fs::rename(&tmp_path, &path)?;
+        self.file.set_len(0)?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+}
+
+fn snapshot_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".snapshot");
+    path.into()
+}
+
+fn checksum(payload: &[u8]) -> u64 {
+    let mut hasher = StableHasher::default();
+    hasher.write(payload);
+    hasher.finish()
+}
+
+/// Returns the payload's length and checksum from the header at the start of
+/// `bytes`, if it is complete and valid.
+fn read_header(bytes: &[u8]) -> Option<(usize, u64)> {
+    let header = bytes.get(..HEADER_SIZE)?;
+    let sum = u64::from_le_bytes(header[12..].try_into().unwrap());
+    if checksum(&header[..12]) != sum {
+        return None;
+    }
+    let length = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+    let payload_sum = u64::from_le_bytes(header[4..12].try_into().unwrap());
+    Some((length, payload_sum))
+}
+
+/// Returns the payload of the record at the start of `bytes`, if it is
+/// complete and valid.
+fn read_record(bytes: &[u8]) -> Option<&[u8]> {
+    let (length, sum) = read_header(bytes)?;
+    let payload = bytes.get(HEADER_SIZE..HEADER_SIZE + length)?;
+    (checksum(payload) == sum).then_some(payload)
+}
+
+/// Returns `true` if the invalid record at the start of `bytes` is the last
+/// one and might have been torn by a crash.
+fn is_torn(bytes: &[u8]) -> bool {
+    if bytes.len() < HEADER_SIZE || bytes.iter().all(|b| *b == 0) {
+        return true;
+    }
+    match read_header(bytes) {
+        Some((length, _)) => bytes.len() < HEADER_SIZE + length,
+        None => false,
+    }
+}
+
+/// Represents errors that can occur when reading or writing an `OpLog`.
+pub enum OpLogError<A, T> {
+    Io(io::Error),
+    /// The record at the given byte offset is invalid, but not a torn last
+    /// record.
+    Corrupt(u64),
+    /// An op or snapshot could not be encoded.
+    Encode(String),
+    /// A logged op or the snapshot could not be decoded.
+    Decode(String),
+    /// A logged op could not be applied.
+    Apply(ChronofoldError<A, T>),
+}
+
+impl<A, T> From<io::Error> for OpLogError<A, T> {
+    fn from(err: io::Error) -> Self {
+        OpLogError::Io(err)
+    }
+}
+
+impl<A, T> fmt::Debug for OpLogError<A, T>
+where
+    A: fmt::Debug + fmt::Display + Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use OpLogError::*;
+        match self {
+            Io(err) => f.debug_tuple("Io").field(err).finish(),
+            Corrupt(offset) => f.debug_tuple("Corrupt").field(offset).finish(),
+            Encode(err) => f.debug_tuple("Encode").field(err).finish(),
+            Decode(err) => f.debug_tuple("Decode").field(err).finish(),
+            Apply(err) => f.debug_tuple("Apply").field(err).finish(),
+        }
+    }
+}
+
+impl<A, T> fmt::Display for OpLogError<A, T>
+where
+    A: fmt::Debug + fmt::Display + Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use OpLogError::*;
+        match self {
+            Io(err) => write!(f, "{err}"),
+            Corrupt(offset) => write!(f, "corrupt record at byte {offset}"),
+            Encode(err) => write!(f, "failed to encode: {err}"),
+            Decode(err) => write!(f, "invalid data: {err}"),
+            Apply(err) => write!(f, "rejected op: {err}"),
+        }
+    }
+}
+
+impl<A, T> std::error::Error for OpLogError<A, T> where A: fmt::Debug + fmt::Display + Copy {}
@@ -0,0 +1,85 @@
+use crate::{Author, Change, Chronofold, ChronofoldError, IntoLocalValue, LogIndex, Op, Storage};
+
+/// The effect of applying an op.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Effect {
+    pub(crate) index: LogIndex,
+    pub(crate) changed: bool,
+}
+
+impl Effect {
+    /// Returns the log index of the resulting change.
+    pub fn index(&self) -> LogIndex {
+        self.index
+    }
+
+    /// Returns `true` if the op changed the elements of the chronofold.
+    ///
+    /// This is `false` for roots and deletions of elements that were deleted
+    /// already.
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Returns the position of the inserted or deleted element among the
+    /// elements of `cfold`, the chronofold the op was applied to.
+    ///
+    /// This is `None` if the op didn't change the elements. The position is
+    /// computed from the current state of `cfold`, so call this before
+    /// applying further ops. It takes linear time, which is why `apply`
+    /// doesn't compute it.
+    pub fn position<A, T, S>(&self, cfold: &Chronofold<A, T, S>) -> Option<usize>
+    where
+        A: Author,
+        S: Storage<A, T>,
+    {
+        if !self.changed {
+            return None;
+        }
+        let element = match *cfold.storage.get(self.index)? {
+            (Change::Delete, _) => cfold.storage.reference(self.index)?,
+            _ => self.index,
+        };
+        Some(
+            cfold
+                .iter_log_indices_causal_range(..)
+                .take_while(|idx| *idx != element)
+                .filter(|idx| cfold.is_element(*idx))
+                .count(),
+        )
+    }
+}
+
+/// The outcome of successfully applying an op with `apply_idempotent`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ApplyOutcome {
+    /// The op was applied with the given effect.
+    Applied(Effect),
+    /// The op was applied before.
+    AlreadyKnown,
+}
+
+impl<A: Author, T, S: Storage<A, T>> Chronofold<A, T, S> {
+    /// Applies an op to the chronofold, treating ops applied before as
+    /// success.
+    ///
+    /// This is useful if ops are delivered at least once. Unlike with
+    /// `apply`, a re-delivered op results in `ApplyOutcome::AlreadyKnown`
+    /// instead of `ChronofoldError::ExistingTimestamp`. Like with
+    /// `apply_checked`, different ops with the same timestamp are reported as
+    /// `ChronofoldError::Equivocation`.
+    pub fn apply_idempotent<V>(
+        &mut self,
+        op: Op<A, V>,
+    ) -> Result<ApplyOutcome, ChronofoldError<A, V>>
+    where
+        V: IntoLocalValue<A, T, S> + Clone,
+        T: PartialEq + Clone + Into<V>,
+    {
+        match self.apply_checked(op) {
+            Ok(effect) => Ok(ApplyOutcome::Applied(effect)),
+            Err(ChronofoldError::ExistingTimestamp(_)) => Ok(ApplyOutcome::AlreadyKnown),
+            Err(err) => Err(err),
+        }
+    }
+}
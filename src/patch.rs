@@ -0,0 +1,397 @@
+//! Unified diffs for text chronofolds.
+
+use std::collections::HashSet;
+
+use crate::{Author, Change, Chronofold, LogIndex, PatchError, Session, Storage, Version};
+
+/// Number of unchanged lines shown before and after each change.
+const CONTEXT: usize = 3;
+
+impl<A: Author, S: Storage<A, char>> Chronofold<A, char, S> {
+    /// Returns a unified diff transforming the text at version `from` into
+    /// the text at version `to`.
+    ///
+    /// The text at a version consists of all inserted characters covered by
+    /// that version, that were not deleted by a change covered by it as well.
+    /// If both texts are equal, the diff is empty.
+    pub fn to_unified_diff(&self, from: &Version<A>, to: &Version<A>) -> String {
+        let old = self.text_at(from);
+        let new = self.text_at(to);
+        let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+        let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+        let edits = diff(&old_lines, &new_lines);
+        if edits.iter().all(|e| matches!(e, Edit::Equal(_, _))) {
+            return String::new();
+        }
+
+        // The 0-based line numbers in the old and new text before each edit.
+        let positions: Vec<(usize, usize)> = edits
+            .iter()
+            .scan((0, 0), |(i, j), edit| {
+                let position = (*i, *j);
+                match edit {
+                    Edit::Equal(_, _) => (*i, *j) = (*i + 1, *j + 1),
+                    Edit::Delete(_) => *i += 1,
+                    Edit::Insert(_) => *j += 1,
+                }
+                Some(position)
+            })
+            .collect();
+
+        let mut result = "--- a\n+++ b\n".to_owned();
+        for hunk in group_hunks(&edits) {
+            let (old_pos, new_pos) = positions[hunk.start];
+            let old_count = edits[hunk.clone()]
+                .iter()
+                .filter(|e| !matches!(e, Edit::Insert(_)))
+                .count();
+            let new_count = edits[hunk.clone()]
+                .iter()
+                .filter(|e| !matches!(e, Edit::Delete(_)))
+                .count();
+            result += &format!(
+                "@@ -{},{} +{},{} @@\n",
+                hunk_start(old_pos, old_count),
+                old_count,
+                hunk_start(new_pos, new_count),
+                new_count
+            );
+            for edit in &edits[hunk] {
+                let (prefix, line) = match *edit {
+                    Edit::Equal(i, _) => (' ', old_lines[i]),
+                    Edit::Delete(i) => ('-', old_lines[i]),
+                    Edit::Insert(j) => ('+', new_lines[j]),
+                };
+                match line.strip_suffix('\n') {
+                    Some(line) => result += &format!("{prefix}{line}\n"),
+                    None => result += &format!("{prefix}{line}\n\\ No newline at end of file\n"),
+                }
+            }
+        }
+        result
+    }
+
+    fn text_at(&self, version: &Version<A>) -> String {
+        let deleted: HashSet<LogIndex> = (0..self.storage.len())
+            .map(LogIndex)
+            .filter(|idx| {
+                matches!(self.storage.get(*idx).as_deref(), Some((Change::Delete, _)))
+                    && version.contains(&self.timestamp(*idx).unwrap())
+            })
+            .filter_map(|idx| self.storage.reference(idx))
+            .collect();
+        self.iter_log_indices_causal_range(..)
+            .filter_map(|idx| match *self.storage.get(idx)? {
+                (Change::Insert(c), _)
+                    if !deleted.contains(&idx)
+                        && version.contains(&self.timestamp(idx).unwrap()) =>
+                {
+                    Some(c)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl<A: Author, S: Storage<A, char>> Session<'_, A, char, S> {
+    /// Applies a unified diff to the current text.
+    ///
+    /// Every hunk's context and removed lines have to match the current text
+    /// at the position given in the hunk's header. The patch is applied either
+    /// completely or not at all: If any hunk doesn't match, no changes are
+    /// made and the numbers of all failing hunks are returned.
+    pub fn apply_patch(&mut self, patch: &str) -> Result<(), PatchError> {
+        let hunks = parse_patch(patch)?;
+
+        let cfold = self.as_ref();
+        let chars: Vec<(char, LogIndex)> = cfold
+            .iter_log_indices_causal_range(..)
+            .filter_map(|idx| Some((cfold.cloned_element(idx)?, idx)))
+            .collect();
+        let mut lines: Vec<(String, Vec<LogIndex>)> = vec![];
+        for (c, idx) in chars {
+            match lines.last_mut() {
+                Some((line, indices)) if !line.ends_with('\n') => {
+                    line.push(c);
+                    indices.push(idx);
+                }
+                _ => lines.push((c.to_string(), vec![idx])),
+            }
+        }
+
+        let mut rejected = vec![];
+        let mut first_unchanged = 0;
+        for (number, hunk) in hunks.iter().enumerate() {
+            let start = hunk.start();
+            let old_lines: Vec<&String> = hunk
+                .lines
+                .iter()
+                .filter(|(kind, _)| *kind != LineKind::Added)
+                .map(|(_, line)| line)
+                .collect();
+            let matches = start >= first_unchanged
+                && start + old_lines.len() <= lines.len()
+                && old_lines
+                    .iter()
+                    .zip(&lines[start..])
+                    .all(|(expected, (actual, _))| *expected == actual);
+            if matches {
+                first_unchanged = start + old_lines.len();
+            } else {
+                rejected.push(number + 1);
+            }
+        }
+        if !rejected.is_empty() {
+            return Err(PatchError::Rejected(rejected));
+        }
+
+        for hunk in hunks.iter() {
+            let start = hunk.start();
+            let mut anchor = match start.checked_sub(1) {
+                Some(previous) => *lines[previous].1.last().unwrap(),
+                None => self.as_ref().root,
+            };
+            let mut current = start;
+            for (kind, line) in hunk.lines.iter() {
+                match kind {
+                    LineKind::Context => {
+                        anchor = *lines[current].1.last().unwrap();
+                        current += 1;
+                    }
+                    LineKind::Removed => {
+                        for idx in lines[current].1.iter() {
+                            self.remove(*idx);
+                        }
+                        current += 1;
+                    }
+                    LineKind::Added => {
+                        for c in line.chars() {
+                            anchor = self.insert_after(anchor, c);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Edit {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Computes a shortest edit script using Myers' algorithm.
+fn diff(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace = vec![];
+
+    'search: for d in 0..=max as isize {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = vec![];
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let i = (k + offset) as usize;
+        let prev_k = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[(prev_k + offset) as usize];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Equal(x as usize, y as usize));
+        }
+        if d > 0 {
+            if x == prev_x {
+                edits.push(Edit::Insert(prev_y as usize));
+            } else {
+                edits.push(Edit::Delete(prev_x as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    edits
+}
+
+/// Groups edits into hunks, returning a range of edits for each hunk.
+fn group_hunks(edits: &[Edit]) -> Vec<std::ops::Range<usize>> {
+    let changes: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !matches!(e, Edit::Equal(_, _)))
+        .map(|(i, _)| i)
+        .collect();
+    let mut hunks: Vec<std::ops::Range<usize>> = vec![];
+    for i in changes {
+        let start = i.saturating_sub(CONTEXT);
+        let end = usize::min(i + CONTEXT + 1, edits.len());
+        match hunks.last_mut() {
+            Some(hunk) if hunk.end >= start => hunk.end = end,
+            _ => hunks.push(start..end),
+        }
+    }
+    hunks
+}
+
+/// Returns the 1-based start line of a hunk.
+///
+/// Empty ranges are denoted by the line before them, which is 0 for the
+/// beginning of the text.
+fn hunk_start(position: usize, count: usize) -> usize {
+    if count == 0 {
+        position
+    } else {
+        position + 1
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum LineKind {
+    Context,
+    Removed,
+    Added,
+}
+
+#[derive(Debug)]
+struct Hunk {
+    old_start: usize,
+    old_count: usize,
+    lines: Vec<(LineKind, String)>,
+}
+
+impl Hunk {
+    /// Returns the 0-based index of the first line affected by this hunk.
+    fn start(&self) -> usize {
+        if self.old_count == 0 {
+            self.old_start
+        } else {
+            self.old_start.saturating_sub(1)
+        }
+    }
+}
+
+fn parse_patch(patch: &str) -> Result<Vec<Hunk>, PatchError> {
+    let mut hunks = vec![];
+    // Unlike `str::lines`, this keeps carriage returns, which are part of the
+    // lines of CRLF text.
+    let mut lines = patch
+        .strip_suffix('\n')
+        .unwrap_or(patch)
+        .split('\n')
+        .enumerate()
+        .peekable();
+    while let Some((number, line)) = lines.next() {
+        if !line.starts_with("@@") {
+            // Skip headers and everything else between hunks.
+            continue;
+        }
+        let malformed = PatchError::Malformed(number + 1);
+        let (old_start, old_count, new_count) =
+            parse_hunk_header(line).ok_or_else(|| malformed.clone())?;
+        let mut hunk = Hunk {
+            old_start,
+            old_count,
+            lines: vec![],
+        };
+        let (mut old_seen, mut new_seen) = (0, 0);
+        while old_seen < old_count || new_seen < new_count {
+            let (number, line) = lines.next().ok_or_else(|| malformed.clone())?;
+            let (kind, content) = match line.chars().next() {
+                Some(' ') => (LineKind::Context, &line[1..]),
+                // Some tools strip the trailing whitespace of empty context
+                // lines.
+                None => (LineKind::Context, ""),
+                Some('-') => (LineKind::Removed, &line[1..]),
+                Some('+') => (LineKind::Added, &line[1..]),
+                _ => return Err(PatchError::Malformed(number + 1)),
+            };
+            if kind != LineKind::Added {
+                old_seen += 1;
+            }
+            if kind != LineKind::Removed {
+                new_seen += 1;
+            }
+            hunk.lines.push((kind, format!("{content}\n")));
+            if let Some((_, marker)) = lines.peek() {
+                if marker.starts_with('\\') {
+                    hunk.lines.last_mut().unwrap().1.pop();
+                    lines.next();
+                }
+            }
+        }
+        if old_seen != old_count || new_seen != new_count {
+            return Err(malformed);
+        }
+        hunks.push(hunk);
+    }
+    Ok(hunks)
+}
+
+/// Parses a hunk header like `@@ -1,3 +1,4 @@`.
+///
+/// Returns the old start line and the line counts of both texts.
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let mut parts = line.strip_prefix("@@ ")?.split(' ');
+    let (old_start, old_count) = parse_range(parts.next()?.strip_prefix('-')?)?;
+    let (_, new_count) = parse_range(parts.next()?.strip_prefix('+')?)?;
+    if parts.next()? != "@@" {
+        return None;
+    }
+    Some((old_start, old_count, new_count))
+}
+
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_lines() {
+        use Edit::*;
+        assert_eq!(
+            vec![Equal(0, 0), Delete(1), Insert(1), Equal(2, 2)],
+            diff(&["a", "b", "c"], &["a", "x", "c"])
+        );
+        assert_eq!(vec![Insert(0)], diff(&[], &["a"]));
+        assert_eq!(Vec::<Edit>::new(), diff(&[], &[]));
+    }
+}
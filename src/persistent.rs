@@ -0,0 +1,224 @@
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::{
+    Author, Change, Chronofold, EarliestDeletion, LendingStorage, LogIndex, Storage, Timestamp,
+};
+
+/// log2 of the number of entries or children per node of a `SnapshotStorage`.
+const BITS: usize = 6;
+
+/// The number of entries or children per node of a `SnapshotStorage`.
+const NODE_SIZE: usize = 1 << BITS;
+
+/// An entry of the log along with its secondary data.
+#[derive(PartialEq, Eq, Clone, Debug)]
+struct Entry<A, T> {
+    change: (Change<T>, EarliestDeletion),
+    id: Timestamp<A>,
+    reference: Option<LogIndex>,
+    next_index: Option<LogIndex>,
+}
+
+/// A node of the tree of entries. All leaves are at the same depth.
+#[derive(PartialEq, Eq, Clone, Debug)]
+enum Node<A, T> {
+    Branch(Vec<Arc<Node<A, T>>>),
+    Leaf(Vec<Entry<A, T>>),
+}
+
+/// A storage sharing its contents with its clones.
+///
+/// The log is stored in a tree whose nodes have up to 64 children or
+/// entries. Nodes are only copied when they are modified while being shared,
+/// so cloning the storage takes constant time, and so does
+/// `Chronofold::snapshot_ref`. Modifying an entry afterwards copies the nodes
+/// on its path, i.e. it takes logarithmic time.
+///
+/// Unlike `MemoryStorage`, this stores the secondary data of every entry, so
+/// it needs more memory.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SnapshotStorage<A, T> {
+    root: Arc<Node<A, T>>,
+    /// The number of levels of branches above the leaves.
+    height: usize,
+    len: usize,
+}
+
+impl<A, T> SnapshotStorage<A, T> {
+    /// Constructs a new, empty storage.
+    pub fn new() -> Self {
+        Self {
+            root: Arc::new(Node::Leaf(vec![])),
+            height: 0,
+            len: 0,
+        }
+    }
+
+    fn entry(&self, index: LogIndex) -> Option<&Entry<A, T>> {
+        if index.0 >= self.len {
+            return None;
+        }
+        let mut node = &*self.root;
+        let mut shift = BITS * self.height;
+        loop {
+            match node {
+                Node::Branch(children) => node = &children[(index.0 >> shift) % NODE_SIZE],
+                Node::Leaf(entries) => return entries.get(index.0 % NODE_SIZE),
+            }
+            shift -= BITS;
+        }
+    }
+}
+
+impl<A: Clone, T: Clone> SnapshotStorage<A, T> {
+    /// Returns a mutable reference to an entry, copying the nodes on its path
+    /// if they are shared.
+    fn entry_mut(&mut self, index: LogIndex) -> &mut Entry<A, T> {
+        let mut node = Arc::make_mut(&mut self.root);
+        let mut shift = BITS * self.height;
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    node = Arc::make_mut(&mut children[(index.0 >> shift) % NODE_SIZE])
+                }
+                Node::Leaf(entries) => return &mut entries[index.0 % NODE_SIZE],
+            }
+            shift -= BITS;
+        }
+    }
+}
+
+impl<A, T> Default for SnapshotStorage<A, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Author, T: Clone> Storage<A, T> for SnapshotStorage<A, T> {
+    type Entry<'a>
+        = &'a (Change<T>, EarliestDeletion)
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: LogIndex) -> Option<Self::Entry<'_>> {
+        self.get_ref(index)
+    }
+
+    fn push(
+        &mut self,
+        change: Change<T>,
+        id: Timestamp<A>,
+        reference: Option<LogIndex>,
+        next_index: Option<LogIndex>,
+    ) {
+        let entry = Entry {
+            change: (change, None),
+            id,
+            reference,
+            next_index,
+        };
+        if self.len == NODE_SIZE << (BITS * self.height) {
+            self.root = Arc::new(Node::Branch(vec![self.root.clone()]));
+            self.height += 1;
+        }
+        let index = self.len;
+        self.len += 1;
+        let mut node = Arc::make_mut(&mut self.root);
+        let mut shift = BITS * self.height;
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    let child = (index >> shift) % NODE_SIZE;
+                    if child == children.len() {
+                        children.push(Arc::new(match shift {
+                            BITS => Node::Leaf(Vec::with_capacity(NODE_SIZE)),
+                            _ => Node::Branch(Vec::with_capacity(NODE_SIZE)),
+                        }));
+                    }
+                    node = Arc::make_mut(&mut children[child]);
+                }
+                Node::Leaf(entries) => return entries.push(entry),
+            }
+            shift -= BITS;
+        }
+    }
+
+    fn timestamp(&self, index: LogIndex) -> Option<Timestamp<A>> {
+        self.entry(index).map(|e| e.id)
+    }
+
+    fn reference(&self, index: LogIndex) -> Option<LogIndex> {
+        self.entry(index)?.reference
+    }
+
+    fn next_index(&self, index: LogIndex) -> Option<LogIndex> {
+        self.entry(index)?.next_index
+    }
+
+    fn set_next_index(&mut self, index: LogIndex, next_index: Option<LogIndex>) {
+        self.entry_mut(index).next_index = next_index;
+    }
+
+    fn set_earliest_deletion(&mut self, index: LogIndex, deletion: EarliestDeletion) {
+        self.entry_mut(index).change.1 = deletion;
+    }
+}
+
+impl<A: Author, T: Clone> LendingStorage<A, T> for SnapshotStorage<A, T> {
+    fn get_ref(&self, index: LogIndex) -> Option<&(Change<T>, EarliestDeletion)> {
+        self.entry(index).map(|e| &e.change)
+    }
+}
+
+impl<A: Author, T: Clone> Chronofold<A, T, SnapshotStorage<A, T>> {
+    /// Returns a read-only snapshot of the chronofold's current state.
+    ///
+    /// The snapshot shares the log with the chronofold, so this takes
+    /// constant time regardless of the log's size. Later changes to the
+    /// chronofold don't affect the snapshot, they copy the parts of the log
+    /// they modify (see `SnapshotStorage`).
+    ///
+    /// This is only available for chronofolds using `SnapshotStorage`, which
+    /// stores a full timestamp, reference and next index for every entry. So
+    /// it needs more memory than `MemoryStorage`, which only stores those
+    /// deviating from the common case of sequential typing.
+    ///
+    /// ```rust
+    /// use chronofold::{Chronofold, SnapshotStorage};
+    ///
+    /// let mut cfold = Chronofold::<u8, char, _>::new_in(0, SnapshotStorage::new());
+    /// cfold.session(1).extend("Hello".chars());
+    /// let snapshot = cfold.snapshot_ref();
+    /// cfold.session(1).extend(" world".chars());
+    ///
+    /// assert_eq!("Hello", format!("{}", *snapshot));
+    /// assert_eq!("Hello world", format!("{cfold}"));
+    /// ```
+    pub fn snapshot_ref(&self) -> SnapshotRef<A, T> {
+        SnapshotRef {
+            cfold: self.clone(),
+        }
+    }
+}
+
+/// A read-only snapshot of a chronofold.
+///
+/// This struct is created by the `snapshot_ref` method on `Chronofold`. It
+/// dereferences to the chronofold as it was at that time.
+#[derive(Clone, Debug)]
+pub struct SnapshotRef<A, T> {
+    cfold: Chronofold<A, T, SnapshotStorage<A, T>>,
+}
+
+impl<A, T> Deref for SnapshotRef<A, T> {
+    type Target = Chronofold<A, T, SnapshotStorage<A, T>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.cfold
+    }
+}
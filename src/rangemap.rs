@@ -22,6 +22,17 @@ impl<K: Ord, V> RangeFromMap<K, V> {
     {
         self.map.range(..=key).map(|(_, v)| v).next_back()
     }
+
+    /// Returns an iterator over the keys values were set for and their values.
+    #[cfg_attr(not(feature = "rkyv"), allow(dead_code))]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.map.iter()
+    }
+
+    /// Returns the greatest key a value was set for.
+    pub(crate) fn last_key(&self) -> Option<&K> {
+        self.map.keys().next_back()
+    }
 }
 
 impl<K: Ord, V: Eq> RangeFromMap<K, V> {
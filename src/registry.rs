@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::Hasher;
+
+use crate::digest::StableHasher;
+use crate::{Op, OpPayload, Timestamp};
+
+/// A compact author id, see `AuthorRegistry`.
+///
+/// Ids are derived from the authors themselves, so they are the same on all
+/// replicas and concurrent ops are ordered the same everywhere. As they are
+/// 128-bit hashes, different authors are very unlikely to have the same id:
+/// among `n` authors, the probability of a collision is about `n² / 2¹²⁹`,
+/// i.e. below 10⁻²⁰ for a billion authors. The hash isn't cryptographic, so
+/// authors chosen by an attacker might still collide.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct AuthorId(pub u128);
+
+impl AuthorId {
+    /// Returns the id of `author`, made of two differently seeded 64-bit
+    /// FNV-1a hashes of its bytes.
+    pub fn of<R: AsRef<[u8]>>(author: &R) -> Self {
+        let hash = |seed| {
+            let mut hasher = StableHasher::with_seed(seed);
+            hasher.write(author.as_ref());
+            u128::from(hasher.finish())
+        };
+        Self(hash(0) << 64 | hash(1))
+    }
+}
+
+impl fmt::Display for AuthorId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+/// A mapping between authors which aren't `Copy`, e.g. `String`s, and
+/// `AuthorId`s.
+///
+/// Chronofolds use the ids as authors, which also keeps versions and
+/// secondary logs small. Ops are converted at the boundary: `resolve_op`
+/// before sending them, `intern_op` after receiving them.
+///
+/// An author whose id collides with the one of an already registered author
+/// can't be registered (see `AuthorId` for how likely that is). Ops by or
+/// referencing such an author are rejected by `intern_op`, and so are all
+/// ops depending on them, so the replica can't sync with that author. Which
+/// of the two authors is rejected depends on the order in which a replica
+/// learns about them, so replicas may reject different ops. The rejected op
+/// is returned in `RejectedOp`, which allows callers to quarantine it.
+///
+/// ```rust
+/// use chronofold::{AuthorId, AuthorRegistry, Chronofold, Op};
+///
+/// let mut registry = AuthorRegistry::new();
+/// let alice = registry.intern("alice".to_string()).unwrap();
+///
+/// let mut cfold = Chronofold::<AuthorId, char>::new(alice);
+/// cfold.session(alice).extend("Hi!".chars());
+/// let ops: Vec<Op<String, char>> = cfold
+///     .iter_ops(..)
+///     .map(|op| registry.resolve_op(Op::cloned(op)).unwrap())
+///     .collect();
+/// assert_eq!("alice", ops[0].id.1);
+///
+/// let mut other_registry = AuthorRegistry::new();
+/// let mut replica = Chronofold::<AuthorId, char>::empty();
+/// for op in ops {
+///     replica.apply(other_registry.intern_op(op).unwrap()).unwrap();
+/// }
+/// assert_eq!("Hi!", format!("{replica}"));
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct AuthorRegistry<R> {
+    authors: BTreeMap<AuthorId, R>,
+}
+
+impl<R: AsRef<[u8]> + PartialEq> AuthorRegistry<R> {
+    /// Constructs a new, empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `author` to the registry, if it isn't yet, and returns its id.
+    ///
+    /// Fails if a different author has the same id.
+    pub fn intern(&mut self, author: R) -> Result<AuthorId, RegistryError> {
+        let id = AuthorId::of(&author);
+        match self.authors.get(&id) {
+            Some(existing) if *existing != author => Err(RegistryError::Collision(id)),
+            Some(_) => Ok(id),
+            None => {
+                self.authors.insert(id, author);
+                Ok(id)
+            }
+        }
+    }
+
+    /// Returns the id of `author`, if it is in the registry.
+    pub fn id(&self, author: &R) -> Option<AuthorId> {
+        let id = AuthorId::of(author);
+        match self.authors.get(&id) {
+            Some(existing) if existing == author => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Returns the author with the given id.
+    pub fn resolve(&self, id: AuthorId) -> Option<&R> {
+        self.authors.get(&id)
+    }
+
+    /// Returns the number of authors in the registry.
+    pub fn len(&self) -> usize {
+        self.authors.len()
+    }
+
+    /// Returns `true` if the registry contains no authors.
+    pub fn is_empty(&self) -> bool {
+        self.authors.is_empty()
+    }
+
+    /// Returns an iterator over ids and authors, ordered by id.
+    pub fn iter(&self) -> impl Iterator<Item = (AuthorId, &R)> {
+        self.authors.iter().map(|(id, author)| (*id, author))
+    }
+
+    /// Converts an op with author ids into one with authors.
+    pub fn resolve_op<T>(&self, op: Op<AuthorId, T>) -> Result<Op<R, T>, RejectedOp<AuthorId, T>>
+    where
+        R: Clone,
+    {
+        let unknown = authors(&op).copied().find(|id| self.resolve(*id).is_none());
+        if let Some(id) = unknown {
+            let error = RegistryError::UnknownId(id);
+            return Err(RejectedOp { error, op });
+        }
+        Ok(map_authors(op, |id| self.authors[&id].clone()))
+    }
+
+    /// Converts an op with authors into one with author ids, adding unknown
+    /// authors to the registry.
+    ///
+    /// If an author collides with another one, the registry is left
+    /// unchanged.
+    pub fn intern_op<T>(&mut self, op: Op<R, T>) -> Result<Op<AuthorId, T>, RejectedOp<R, T>> {
+        if let Some(id) = self.collision(&op) {
+            let error = RegistryError::Collision(id);
+            return Err(RejectedOp { error, op });
+        }
+        Ok(map_authors(op, |author| self.intern(author).unwrap()))
+    }
+
+    /// Returns the id of an author of `op` colliding with another author.
+    fn collision<T>(&self, op: &Op<R, T>) -> Option<AuthorId> {
+        let mut new = BTreeMap::new();
+        for author in authors(op) {
+            let id = AuthorId::of(author);
+            match self.authors.get(&id).or_else(|| new.get(&id).copied()) {
+                Some(existing) if existing != author => return Some(id),
+                Some(_) => {}
+                None => {
+                    new.insert(id, author);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<R> Default for AuthorRegistry<R> {
+    fn default() -> Self {
+        Self {
+            authors: BTreeMap::new(),
+        }
+    }
+}
+
+/// Returns the authors of an op's id and reference.
+fn authors<A, T>(op: &Op<A, T>) -> impl Iterator<Item = &A> {
+    let reference = match &op.payload {
+        OpPayload::Root | OpPayload::Insert(None, _) => None,
+        OpPayload::Insert(Some(reference), _) | OpPayload::Delete(reference) => Some(reference),
+    };
+    std::iter::once(&op.id.1).chain(reference.map(|t| &t.1))
+}
+
+fn map_authors<A, B, T, F>(op: Op<A, T>, mut f: F) -> Op<B, T>
+where
+    F: FnMut(A) -> B,
+{
+    let mut map = |t: Timestamp<A>| Timestamp(t.0, f(t.1));
+    let id = map(op.id);
+    let payload = match op.payload {
+        OpPayload::Root => OpPayload::Root,
+        OpPayload::Insert(reference, value) => OpPayload::Insert(reference.map(&mut map), value),
+        OpPayload::Delete(reference) => OpPayload::Delete(map(reference)),
+    };
+    Op::new(id, payload)
+}
+
+/// Represents errors that can occur when mapping authors to ids or back.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum RegistryError {
+    /// A different author with the same id is already registered.
+    Collision(AuthorId),
+    /// No author with this id is registered.
+    UnknownId(AuthorId),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use RegistryError::*;
+        match self {
+            Collision(id) => write!(f, "another author has the id {id}"),
+            UnknownId(id) => write!(f, "unknown author id {id}"),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// An op that could not be converted by an `AuthorRegistry`, e.g. to be
+/// quarantined.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct RejectedOp<A, T> {
+    pub error: RegistryError,
+    pub op: Op<A, T>,
+}
+
+impl<A, T> fmt::Display for RejectedOp<A, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "rejected op: {}", self.error)
+    }
+}
+
+impl<A: fmt::Debug, T: fmt::Debug> std::error::Error for RejectedOp<A, T> {}
+
+/// Registries are serialized as lists of authors, their ids are derived on
+/// deserialization.
+#[cfg(feature = "serde")]
+mod serde {
+    use super::AuthorRegistry;
+    use serde::de::Error;
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    impl<R: Serialize> Serialize for AuthorRegistry<R> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut seq = serializer.serialize_seq(Some(self.authors.len()))?;
+            for author in self.authors.values() {
+                seq.serialize_element(author)?;
+            }
+            seq.end()
+        }
+    }
+
+    impl<'de, R> Deserialize<'de> for AuthorRegistry<R>
+    where
+        R: Deserialize<'de> + AsRef<[u8]> + PartialEq,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let mut registry = AuthorRegistry::new();
+            for author in Vec::<R>::deserialize(deserializer)? {
+                registry.intern(author).map_err(D::Error::custom)?;
+            }
+            Ok(registry)
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+use crate::{Author, ChronofoldError, LogIndex, Op, OpPayload, Timestamp, Version};
+
+/// A store for ops whose values are never inspected.
+///
+/// Unlike a chronofold, a relay doesn't need to know what the values of
+/// inserts mean, so they can be opaque bytes, e.g. if they are end-to-end
+/// encrypted. It accepts exactly the ops a chronofold with the same ops would
+/// accept, i.e. duplicates and ops whose causal dependencies are missing are
+/// rejected, and it can tell peers which ops they are missing.
+///
+/// ```rust
+/// use chronofold::{LogIndex, Op, Relay, Timestamp, Version};
+///
+/// let mut relay = Relay::<u8>::new();
+/// relay.apply(Op::root(Timestamp(LogIndex(0), 0))).unwrap();
+/// relay
+///     .apply(Op::insert(
+///         Timestamp(LogIndex(1), 1),
+///         Some(Timestamp(LogIndex(0), 0)),
+///         vec![0x2a, 0x17],
+///     ))
+///     .unwrap();
+///
+/// assert_eq!(2, relay.iter_newer_ops(&Version::new()).count());
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Relay<A, P = Vec<u8>> {
+    ops: Vec<Op<A, P>>,
+    version: Version<A>,
+    indices: BTreeMap<Timestamp<A>, usize>,
+}
+
+impl<A: Author, P> Relay<A, P> {
+    /// Constructs a new, empty relay.
+    pub fn new() -> Self {
+        Self {
+            ops: vec![],
+            version: Version::new(),
+            indices: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the number of ops in the relay.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns `true` if the relay contains no ops.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Returns a vector clock representing the ops in the relay.
+    pub fn version(&self) -> &Version<A> {
+        &self.version
+    }
+
+    /// Returns the op with the given timestamp.
+    pub fn get(&self, id: &Timestamp<A>) -> Option<&Op<A, P>> {
+        self.indices.get(id).map(|i| &self.ops[*i])
+    }
+
+    /// Returns an iterator over all ops in the order they were applied, which
+    /// is a causal order.
+    pub fn iter_ops(&self) -> impl Iterator<Item = &Op<A, P>> {
+        self.ops.iter()
+    }
+
+    /// Returns an iterator over ops newer than the given version in the order
+    /// they were applied.
+    pub fn iter_newer_ops<'a>(
+        &'a self,
+        version: &'a Version<A>,
+    ) -> impl Iterator<Item = &'a Op<A, P>> + 'a {
+        self.ops.iter().filter(move |op| !version.contains(&op.id))
+    }
+}
+
+impl<A: Author, P: PartialEq + Clone> Relay<A, P> {
+    /// Adds an op to the relay.
+    ///
+    /// This performs the same checks as `Chronofold::apply_checked`, based only on
+    /// the timestamps and references of the ops. That includes reporting
+    /// different ops with the same timestamp as
+    /// `ChronofoldError::Equivocation`.
+    pub fn apply(&mut self, op: Op<A, P>) -> Result<(), ChronofoldError<A, P>> {
+        if let Some(existing) = self.get(&op.id) {
+            return Err(if *existing == op {
+                ChronofoldError::ExistingTimestamp(op)
+            } else {
+                ChronofoldError::Equivocation {
+                    existing: existing.clone(),
+                    received: op,
+                }
+            });
+        }
+        if op.id.0 > self.next_timestamp_index() {
+            return Err(ChronofoldError::FutureTimestamp(op));
+        }
+
+        use OpPayload::*;
+        let reference = match &op.payload {
+            Root if !self.ops.is_empty() => return Err(ChronofoldError::MalformedRoot(op)),
+            Root => None,
+            Insert(None, _) => return Err(ChronofoldError::InvalidReferenceKind(op)),
+            Insert(Some(t), _) | Delete(t) => Some(*t),
+        };
+        if let Some(t) = reference {
+            match (&op.payload, self.get(&t).map(|r| &r.payload)) {
+                (_, None) => return Err(ChronofoldError::UnknownReference(op)),
+                (Delete(_), Some(Root)) => return Err(ChronofoldError::DeleteOfRoot(op)),
+                (Delete(_), Some(Delete(_))) => {
+                    return Err(ChronofoldError::InvalidReferenceKind(op))
+                }
+                _ => {}
+            }
+        }
+
+        self.indices.insert(op.id, self.ops.len());
+        self.version.inc(&op.id);
+        self.ops.push(op);
+        Ok(())
+    }
+
+    /// Returns the index the next local timestamp of a chronofold with the
+    /// same ops would have.
+    fn next_timestamp_index(&self) -> LogIndex {
+        let max_version = self.version.iter().map(|t| t.0 .0 + 1).max();
+        LogIndex(usize::max(self.ops.len(), max_version.unwrap_or(0)))
+    }
+}
+
+impl<A: Author, P> Default for Relay<A, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
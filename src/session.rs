@@ -1,6 +1,9 @@
 use std::ops::{Bound, RangeBounds};
 
-use crate::{Author, Change, Chronofold, FromLocalValue, LogIndex, Op, Timestamp};
+use crate::{
+    Author, Change, Chronofold, ChronofoldError, FromLocalValue, LendingStorage, LogIndex,
+    MemoryStorage, Op, Storage, Timestamp,
+};
 
 /// An editing session tied to one author.
 ///
@@ -11,16 +14,19 @@ use crate::{Author, Change, Chronofold, FromLocalValue, LogIndex, Op, Timestamp}
 /// Note that `Session` has a mutable (exclusive) borrow of a chronofold. So
 /// Rust's ownership rules enforce that there is always just one `Session` per
 /// chronofold.
+///
+/// Editing a chronofold without a root, e.g. one created by
+/// `Chronofold::empty`, panics. Use `create_root` first.
 #[derive(Debug)]
-pub struct Session<'a, A, T> {
-    chronofold: &'a mut Chronofold<A, T>,
+pub struct Session<'a, A, T, S = MemoryStorage<A, T>> {
+    chronofold: &'a mut Chronofold<A, T, S>,
     author: A,
     first_index: LogIndex,
 }
 
-impl<'a, A: Author, T> Session<'a, A, T> {
+impl<'a, A: Author, T, S: Storage<A, T>> Session<'a, A, T, S> {
     /// Creates an editing session for a single author.
-    pub fn new(author: A, chronofold: &'a mut Chronofold<A, T>) -> Self {
+    pub fn new(author: A, chronofold: &'a mut Chronofold<A, T, S>) -> Self {
         let first_index = chronofold.next_log_index();
         Self {
             chronofold,
@@ -31,11 +37,7 @@ impl<'a, A: Author, T> Session<'a, A, T> {
 
     /// Clears the chronofold, removing all elements.
     pub fn clear(&mut self) {
-        let indices = self
-            .chronofold
-            .iter()
-            .map(|(_, idx)| idx)
-            .collect::<Vec<_>>();
+        let indices = self.chronofold.iter_element_indices(..).collect::<Vec<_>>();
         for idx in indices {
             self.remove(idx);
         }
@@ -44,7 +46,7 @@ impl<'a, A: Author, T> Session<'a, A, T> {
     /// Appends an element to the back of the chronofold and returns the new
     /// element's log index.
     pub fn push_back(&mut self, value: T) -> LogIndex {
-        if let Some((_, last_index)) = self.chronofold.iter().last() {
+        if let Some(last_index) = self.chronofold.iter_element_indices(..).last() {
             self.insert_after(last_index, value)
         } else {
             // no non-deleted entries left
@@ -77,7 +79,7 @@ impl<'a, A: Author, T> Session<'a, A, T> {
     /// Extends the chronofold with the contents of `iter`, returns the log
     /// index of the last inserted element, if any.
     pub fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Option<LogIndex> {
-        let oob = LogIndex(self.chronofold.log.len());
+        let oob = self.chronofold.next_log_index();
         self.splice(oob..oob, iter)
     }
 
@@ -92,34 +94,32 @@ impl<'a, A: Author, T> Session<'a, A, T> {
     {
         let last_idx = match range.start_bound() {
             Bound::Unbounded => None,
-            Bound::Included(idx) => self.chronofold.index_before(*idx),
+            Bound::Included(idx) => self.chronofold.element_before(*idx),
             Bound::Excluded(idx) => Some(*idx),
         }
         .unwrap_or(self.as_ref().root);
-        let to_remove: Vec<LogIndex> = self
-            .chronofold
-            .iter_range(range)
-            .map(|(_, idx)| idx)
-            .collect();
+        let to_remove: Vec<LogIndex> = self.chronofold.iter_element_indices(range).collect();
         for idx in to_remove.into_iter() {
             self.remove(idx);
         }
         self.apply_changes(last_idx, replace_with.into_iter().map(Change::Insert))
     }
 
-    pub fn create_root(&mut self) -> LogIndex {
-        let new_index = LogIndex(self.chronofold.log.len());
-        self.chronofold
-            .apply_change(Timestamp(new_index, self.author), None, Change::Root)
+    /// Creates a root in an empty chronofold, e.g. one created by `empty`.
+    ///
+    /// Fails with `ChronofoldError::MalformedRoot` if the chronofold is not
+    /// empty.
+    pub fn create_root(&mut self) -> Result<LogIndex, ChronofoldError<A, T>> {
+        let id = Timestamp(self.chronofold.next_timestamp_index(), self.author);
+        if !self.chronofold.storage.is_empty() {
+            return Err(ChronofoldError::MalformedRoot(Op::root(id)));
+        }
+        Ok(self.chronofold.apply_change(id, None, Change::Root))
     }
 
+    /// Inserts an element at the beginning, like `push_front`.
     pub fn insert(&mut self, value: T) -> LogIndex {
-        let new_index = LogIndex(self.chronofold.log.len());
-        self.chronofold.apply_change(
-            Timestamp(new_index, self.author),
-            None,
-            Change::Insert(value),
-        )
+        self.push_front(value)
     }
 
     fn apply_change(&mut self, reference: LogIndex, change: Change<T>) -> LogIndex {
@@ -133,12 +133,14 @@ impl<'a, A: Author, T> Session<'a, A, T> {
         self.chronofold
             .apply_local_changes(self.author, reference, changes)
     }
+}
 
+impl<'a, A: Author, T, S: LendingStorage<A, T>> Session<'a, A, T, S> {
     /// Returns an iterator over ops in log order, that where created in this
     /// session.
     pub fn iter_ops<V>(&'a self) -> impl Iterator<Item = Op<A, V>> + 'a
     where
-        V: FromLocalValue<'a, A, T> + 'a,
+        V: FromLocalValue<'a, A, T, S> + 'a,
     {
         self.chronofold
             .iter_ops(self.first_index..)
@@ -146,14 +148,14 @@ impl<'a, A: Author, T> Session<'a, A, T> {
     }
 }
 
-impl<A: Author, T> AsRef<Chronofold<A, T>> for Session<'_, A, T> {
-    fn as_ref(&self) -> &Chronofold<A, T> {
+impl<A: Author, T, S: Storage<A, T>> AsRef<Chronofold<A, T, S>> for Session<'_, A, T, S> {
+    fn as_ref(&self) -> &Chronofold<A, T, S> {
         self.chronofold
     }
 }
 
-impl<A: Author, T> AsMut<Chronofold<A, T>> for Session<'_, A, T> {
-    fn as_mut(&mut self) -> &mut Chronofold<A, T> {
+impl<A: Author, T, S: Storage<A, T>> AsMut<Chronofold<A, T, S>> for Session<'_, A, T, S> {
+    fn as_mut(&mut self) -> &mut Chronofold<A, T, S> {
         self.chronofold
     }
 }
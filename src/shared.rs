@@ -0,0 +1,155 @@
+use std::fmt;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
+
+use crate::{Author, Chronofold, ChronofoldError, Effect, Op, Session};
+
+/// A function passing ops on to a subscriber, which returns `false` once the
+/// subscriber is gone.
+type SendFn<A, T> = Box<dyn FnMut(Op<A, T>) -> bool + Send>;
+
+struct Subscriber<A, T> {
+    send: SendFn<A, T>,
+    /// Whether only ops produced by `edit` are passed on.
+    local_only: bool,
+}
+
+/// A handle to a chronofold shared between threads.
+///
+/// Any number of threads can read the chronofold concurrently, while edits
+/// are serialized. Every op added to the chronofold, by `edit` or `apply`, is
+/// sent to all subscribers in log order. Cloning the handle doesn't clone the
+/// chronofold.
+///
+/// ```rust
+/// use std::thread;
+///
+/// use chronofold::{Chronofold, SharedChronofold};
+///
+/// let shared = SharedChronofold::new(Chronofold::<u8, char>::default());
+/// let ops = shared.subscribe();
+///
+/// let handle = shared.clone();
+/// thread::spawn(move || handle.edit(1, |session| session.extend("Hi!".chars())))
+///     .join()
+///     .unwrap();
+///
+/// assert_eq!("Hi!", format!("{}", *shared.read()));
+/// assert_eq!(3, ops.try_iter().count());
+/// ```
+pub struct SharedChronofold<A, T> {
+    cfold: Arc<RwLock<Chronofold<A, T>>>,
+    subscribers: Arc<Mutex<Vec<Subscriber<A, T>>>>,
+}
+
+impl<A, T> SharedChronofold<A, T>
+where
+    A: Author + Send + 'static,
+    T: Clone + Send + 'static,
+{
+    /// Constructs a handle sharing `cfold`.
+    pub fn new(cfold: Chronofold<A, T>) -> Self {
+        Self {
+            cfold: Arc::new(RwLock::new(cfold)),
+            subscribers: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Locks the chronofold for reading.
+    ///
+    /// Edits block until the returned guard is dropped.
+    pub fn read(&self) -> RwLockReadGuard<'_, Chronofold<A, T>> {
+        self.cfold.read().expect("chronofold lock poisoned")
+    }
+
+    /// Runs `f` with an editing session for `author` and sends the resulting
+    /// ops to all subscribers.
+    pub fn edit<F, R>(&self, author: A, f: F) -> R
+    where
+        F: FnOnce(&mut Session<'_, A, T>) -> R,
+    {
+        let mut cfold = self.cfold.write().expect("chronofold lock poisoned");
+        let first = cfold.next_log_index();
+        let result = f(&mut cfold.session(author));
+        self.broadcast(cfold.iter_ops(first..).map(Op::cloned), true);
+        result
+    }
+
+    /// Applies an op to the chronofold and sends it to all subscribers.
+    ///
+    /// See `Chronofold::apply_checked`.
+    pub fn apply(&self, op: Op<A, T>) -> Result<Effect, ChronofoldError<A, T>>
+    where
+        T: PartialEq,
+    {
+        let mut cfold = self.cfold.write().expect("chronofold lock poisoned");
+        let effect = cfold.apply_checked(op)?;
+        self.broadcast(cfold.iter_ops(effect.index()..).map(Op::cloned), false);
+        Ok(effect)
+    }
+
+    /// Applies `ops` to the chronofold using `buffer` and sends the ops
+    /// applied to all subscribers, even if one of `ops` is rejected.
+    #[cfg(feature = "futures")]
+    pub(crate) fn apply_buffered<I>(
+        &self,
+        buffer: &mut crate::CausalBuffer<A, T>,
+        ops: I,
+    ) -> Result<(), crate::BufferError<A, T>>
+    where
+        T: PartialEq,
+        I: IntoIterator<Item = Op<A, T>>,
+    {
+        let mut cfold = self.cfold.write().expect("chronofold lock poisoned");
+        let first = cfold.next_log_index();
+        let result = buffer.apply(&mut cfold, ops);
+        self.broadcast(cfold.iter_ops(first..).map(Op::cloned), false);
+        result
+    }
+
+    /// Returns a receiver for all ops added from now on.
+    pub fn subscribe(&self) -> Receiver<Op<A, T>> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribe_with(false, Box::new(move |op| sender.send(op).is_ok()));
+        receiver
+    }
+
+    pub(crate) fn subscribe_with(&self, local_only: bool, send: SendFn<A, T>) {
+        self.subscribers
+            .lock()
+            .expect("subscribers lock poisoned")
+            .push(Subscriber { send, local_only });
+    }
+
+    /// Sends `ops` to all subscribers, removing the ones that are gone.
+    ///
+    /// This has to be called while holding the write lock, so that all
+    /// subscribers receive ops in log order.
+    fn broadcast<I: Iterator<Item = Op<A, T>>>(&self, ops: I, local: bool) {
+        let mut subscribers = self.subscribers.lock().expect("subscribers lock poisoned");
+        for op in ops {
+            subscribers.retain_mut(|s| (s.local_only && !local) || (s.send)(op.clone()));
+        }
+    }
+}
+
+impl<A, T> fmt::Debug for SharedChronofold<A, T>
+where
+    A: fmt::Debug,
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SharedChronofold")
+            .field("cfold", &self.cfold)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A, T> Clone for SharedChronofold<A, T> {
+    fn clone(&self) -> Self {
+        Self {
+            cfold: Arc::clone(&self.cfold),
+            subscribers: Arc::clone(&self.subscribers),
+        }
+    }
+}
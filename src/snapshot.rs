@@ -0,0 +1,91 @@
+use crate::{Author, Change, Chronofold, LogIndex, Storage, Timestamp, Version};
+
+/// A compact state of a chronofold, containing only its visible elements.
+///
+/// Snapshots are meant for bootstrapping new replicas: Unlike the chronofold
+/// itself, they contain neither deletions nor deleted elements. Their history
+/// is lost, i.e. a chronofold created from a snapshot cannot provide ops or
+/// versions predating it.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Snapshot<A, T> {
+    #[cfg_attr(
+        feature = "serde",
+        serde(bound(
+            serialize = "Version<A>: serde::Serialize",
+            deserialize = "Version<A>: serde::Deserialize<'de>"
+        ))
+    )]
+    version: Version<A>,
+    root: Option<Timestamp<A>>,
+    elements: Vec<(Timestamp<A>, T)>,
+}
+
+impl<A, T> Snapshot<A, T> {
+    /// Returns the version of the chronofold this snapshot was taken of.
+    pub fn version(&self) -> &Version<A> {
+        &self.version
+    }
+
+    /// Returns an iterator over the elements and their timestamps.
+    pub fn iter(&self) -> impl Iterator<Item = &(Timestamp<A>, T)> {
+        self.elements.iter()
+    }
+}
+
+impl<A: Author, T: Clone, S: Storage<A, T>> Chronofold<A, T, S> {
+    /// Returns a snapshot of the chronofold's current state.
+    pub fn snapshot(&self) -> Snapshot<A, T> {
+        Snapshot {
+            version: self.version.clone(),
+            root: self.timestamp(self.root),
+            elements: self
+                .iter_log_indices_causal_range(..)
+                .filter_map(|idx| Some((self.timestamp(idx)?, self.cloned_element(idx)?)))
+                .collect(),
+        }
+    }
+}
+
+impl<A: Author, T> Chronofold<A, T> {
+    /// Constructs a chronofold from a snapshot.
+    ///
+    /// The resulting chronofold accepts ops newer than the snapshot's version.
+    /// Ops covered by that version are treated as already applied. Note that
+    /// this is only correct for ops causally newer than the snapshot: Other
+    /// ops might reference deletions or deleted elements, which are not part
+    /// of the snapshot.
+    ///
+    /// This is only available for chronofolds kept in memory.
+    pub fn from_snapshot(snapshot: Snapshot<A, T>) -> Self {
+        let mut cfold = Self::empty();
+        let root = match snapshot.root {
+            Some(root) => root,
+            None => {
+                cfold.version = snapshot.version.clone();
+                cfold.compacted = snapshot.version;
+                return cfold;
+            }
+        };
+
+        // Log order equals causal order, so every element references its
+        // predecessor in the log.
+        let entries = std::iter::once((root, Change::Root)).chain(
+            snapshot
+                .elements
+                .into_iter()
+                .map(|(id, value)| (id, Change::Insert(value))),
+        );
+        for (i, (id, change)) in entries.enumerate() {
+            let reference = i.checked_sub(1).map(LogIndex);
+            cfold
+                .storage
+                .push(change, id, reference, Some(LogIndex(i + 1)));
+        }
+        let last = cfold.last_index().unwrap();
+        cfold.storage.set_next_index(last, None);
+        cfold.version = snapshot.version.clone();
+        cfold.compacted = snapshot.version;
+        cfold
+    }
+}
@@ -0,0 +1,183 @@
+use std::ops::Deref;
+
+use crate::index::{IndexShift, RelativeNextIndex, RelativeReference};
+use crate::offsetmap::OffsetMap;
+use crate::rangemap::RangeFromMap;
+use crate::{Author, Change, EarliestDeletion, InvariantError, LogIndex, Timestamp};
+
+/// A backend holding a chronofold's log and its secondary logs.
+///
+/// Besides the changes themselves, a chronofold stores for every log index
+/// the timestamp of the change, its reference and the next log index in
+/// causal order. Log indices are assigned by `push`, i.e. the `n`th entry
+/// pushed has log index `n - 1`. Entries are never removed and only their
+/// next indices and earliest deletions are changed afterwards.
+///
+/// Changes are returned as `Entry`s, which can be references or values
+/// loaded from elsewhere, so implementations don't have to keep them in
+/// memory. Methods of `Chronofold` lending out values, e.g. `iter`, are only
+/// available for implementations of `LendingStorage`.
+pub trait Storage<A, T> {
+    /// A change and its earliest deletion as returned by `get`.
+    type Entry<'a>: Deref<Target = (Change<T>, EarliestDeletion)>
+    where
+        Self: 'a;
+
+    /// Returns the number of entries in the log.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the log contains no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the change at `index` and its earliest deletion.
+    fn get(&self, index: LogIndex) -> Option<Self::Entry<'_>>;
+
+    /// Appends an entry to the log.
+    fn push(
+        &mut self,
+        change: Change<T>,
+        id: Timestamp<A>,
+        reference: Option<LogIndex>,
+        next_index: Option<LogIndex>,
+    );
+
+    /// Returns the timestamp of the change at `index`.
+    fn timestamp(&self, index: LogIndex) -> Option<Timestamp<A>>;
+
+    /// Returns the log index the change at `index` references.
+    fn reference(&self, index: LogIndex) -> Option<LogIndex>;
+
+    /// Returns the log index following `index` in causal order.
+    fn next_index(&self, index: LogIndex) -> Option<LogIndex>;
+
+    fn set_next_index(&mut self, index: LogIndex, next_index: Option<LogIndex>);
+
+    fn set_earliest_deletion(&mut self, index: LogIndex, deletion: EarliestDeletion);
+
+    /// Checks the consistency of the storage's own data structures, see
+    /// `Chronofold::check_invariants`.
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        Ok(())
+    }
+}
+
+/// A storage lending out references to its changes, e.g. because it keeps
+/// them in memory.
+pub trait LendingStorage<A, T>: Storage<A, T> {
+    /// Returns a reference to the change at `index` and its earliest
+    /// deletion.
+    fn get_ref(&self, index: LogIndex) -> Option<&(Change<T>, EarliestDeletion)>;
+}
+
+/// The default storage, keeping everything in memory.
+///
+/// Secondary logs only store entries differing from what consecutive inserts
+/// by a single author would result in.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct MemoryStorage<A, T> {
+    pub(crate) log: Vec<(Change<T>, EarliestDeletion)>,
+    pub(crate) next_indices: OffsetMap<LogIndex, RelativeNextIndex>,
+    pub(crate) references: OffsetMap<LogIndex, RelativeReference>,
+    pub(crate) authors: RangeFromMap<LogIndex, A>,
+    pub(crate) index_shifts: RangeFromMap<LogIndex, IndexShift>,
+}
+
+impl<A, T> MemoryStorage<A, T> {
+    /// Constructs a new, empty storage.
+    pub fn new() -> Self {
+        Self {
+            log: vec![],
+            next_indices: OffsetMap::default(),
+            references: OffsetMap::default(),
+            authors: RangeFromMap::default(),
+            index_shifts: RangeFromMap::default(),
+        }
+    }
+}
+
+impl<A, T> Default for MemoryStorage<A, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Author, T> Storage<A, T> for MemoryStorage<A, T> {
+    type Entry<'a>
+        = &'a (Change<T>, EarliestDeletion)
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize {
+        self.log.len()
+    }
+
+    fn get(&self, index: LogIndex) -> Option<Self::Entry<'_>> {
+        self.log.get(index.0)
+    }
+
+    fn push(
+        &mut self,
+        change: Change<T>,
+        id: Timestamp<A>,
+        reference: Option<LogIndex>,
+        next_index: Option<LogIndex>,
+    ) {
+        let index = LogIndex(self.log.len());
+        self.log.push((change, None));
+        self.next_indices.set(index, next_index);
+        self.references.set(index, reference);
+        self.authors.set(index, id.1);
+        self.index_shifts.set(index, IndexShift::new(index, id.0));
+    }
+
+    fn timestamp(&self, index: LogIndex) -> Option<Timestamp<A>> {
+        if let (Some(shift), Some(author)) =
+            (self.index_shifts.get(&index), self.authors.get(&index))
+        {
+            Some(Timestamp(&index - shift, *author))
+        } else {
+            None
+        }
+    }
+
+    fn reference(&self, index: LogIndex) -> Option<LogIndex> {
+        self.references.get(&index)
+    }
+
+    fn next_index(&self, index: LogIndex) -> Option<LogIndex> {
+        self.next_indices.get(&index)
+    }
+
+    fn set_next_index(&mut self, index: LogIndex, next_index: Option<LogIndex>) {
+        self.next_indices.set(index, next_index);
+    }
+
+    fn set_earliest_deletion(&mut self, index: LogIndex, deletion: EarliestDeletion) {
+        self.log[index.0].1 = deletion;
+    }
+
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        let last_keys = [
+            self.next_indices.last_key(),
+            self.references.last_key(),
+            self.authors.last_key(),
+            self.index_shifts.last_key(),
+        ];
+        match last_keys
+            .into_iter()
+            .flatten()
+            .find(|key| key.0 >= self.len())
+        {
+            Some(key) => Err(InvariantError::ExcessEntry(*key)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<A: Author, T> LendingStorage<A, T> for MemoryStorage<A, T> {
+    fn get_ref(&self, index: LogIndex) -> Option<&(Change<T>, EarliestDeletion)> {
+        self.log.get(index.0)
+    }
+}
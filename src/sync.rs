@@ -0,0 +1,222 @@
+//! A protocol for synchronizing two replicas.
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{Author, BufferError, CausalBuffer, Chronofold, ChronofoldError, Op, Version};
+
+/// The default maximum number of ops per message.
+const DEFAULT_BATCH_SIZE: usize = 1000;
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "A: Author + Serialize, T: Serialize",
+    deserialize = "A: Author + Deserialize<'de>, T: Deserialize<'de>"
+))]
+enum Message<A, T> {
+    /// The sender's version, starting the exchange.
+    Hello(Version<A>),
+    /// Ops the receiver is missing.
+    Ops(Vec<Op<A, T>>),
+    /// All ops the receiver was missing were sent. After applying them, its
+    /// version is at least the given one.
+    Sent(Version<A>),
+    /// The sender has applied all ops it was missing.
+    Done,
+}
+
+/// One side of a synchronization between two replicas.
+///
+/// Both replicas create a session and send the messages returned by `start`
+/// to each other. Every message received has to be passed to `receive`,
+/// which returns the messages to send in response. Once the session
+/// `is_complete`, both replicas have seen the ops the other one had at the
+/// start of the session.
+///
+/// Messages are plain bytes, so any transport will do. Messages might arrive
+/// out of order or more than once, but must not get lost.
+///
+/// ```rust
+/// use chronofold::{Chronofold, SyncSession};
+///
+/// let mut cfold_a = Chronofold::<u8, char>::default();
+/// cfold_a.session(1).extend("Hello".chars());
+/// let mut cfold_b = cfold_a.clone();
+/// cfold_b.session(2).extend(" world!".chars());
+///
+/// let mut session_a = SyncSession::new();
+/// let mut session_b = SyncSession::new();
+/// let mut to_b = session_a.start(&cfold_a);
+/// let mut to_a = session_b.start(&cfold_b);
+/// while !to_a.is_empty() || !to_b.is_empty() {
+///     for message in std::mem::take(&mut to_b) {
+///         to_a.extend(session_b.receive(&mut cfold_b, &message).unwrap());
+///     }
+///     for message in std::mem::take(&mut to_a) {
+///         to_b.extend(session_a.receive(&mut cfold_a, &message).unwrap());
+///     }
+/// }
+///
+/// assert!(session_a.is_complete() && session_b.is_complete());
+/// assert_eq!("Hello world!", format!("{cfold_a}"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct SyncSession<A, T> {
+    batch_size: usize,
+    announced: Option<Version<A>>,
+    done_sent: bool,
+    done_received: bool,
+    pending: CausalBuffer<A, T>,
+}
+
+impl<A, T> SyncSession<A, T>
+where
+    A: Author + Serialize + DeserializeOwned,
+    T: Clone + PartialEq + Serialize + DeserializeOwned,
+{
+    pub fn new() -> Self {
+        Self {
+            batch_size: DEFAULT_BATCH_SIZE,
+            announced: None,
+            done_sent: false,
+            done_received: false,
+            pending: CausalBuffer::new(),
+        }
+    }
+
+    /// Sets the maximum number of ops sent in a single message.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = usize::max(batch_size, 1);
+        self
+    }
+
+    /// Sets the maximum number of ops kept until the ops they depend on
+    /// arrive.
+    ///
+    /// This bounds the memory a peer can make the session use by sending ops
+    /// with unknown references.
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.pending = self.pending.with_max_pending(max_pending);
+        self
+    }
+
+    /// Starts the session and returns the messages to send.
+    pub fn start(&mut self, cfold: &Chronofold<A, T>) -> Vec<Vec<u8>> {
+        vec![encode(&Message::<A, T>::Hello(cfold.version().clone()))]
+    }
+
+    /// Handles a message and returns the messages to send in response.
+    ///
+    /// Ops that can't be applied yet are kept until the ops they depend on
+    /// arrive. If there are more of them than allowed by `with_max_pending`,
+    /// they are discarded and `SyncError::TooManyPending` is returned. The
+    /// session can't complete after that.
+    pub fn receive(
+        &mut self,
+        cfold: &mut Chronofold<A, T>,
+        message: &[u8],
+    ) -> Result<Vec<Vec<u8>>, SyncError<A, T>> {
+        let message: Message<A, T> =
+            serde_json::from_slice(message).map_err(|err| SyncError::Malformed(err.to_string()))?;
+        let mut responses = vec![];
+        match message {
+            Message::Hello(version) => {
+                let ops: Vec<Op<A, T>> = cfold
+                    .iter_newer_ops::<&T>(&version)
+                    .map(Op::cloned)
+                    .collect();
+                for batch in ops.chunks(self.batch_size) {
+                    responses.push(encode(&Message::Ops(batch.to_vec())));
+                }
+                responses.push(encode(&Message::<A, T>::Sent(cfold.version().clone())));
+            }
+            Message::Ops(ops) => self.pending.apply(cfold, ops)?,
+            Message::Sent(version) => self.announced = Some(version),
+            Message::Done => self.done_received = true,
+        }
+
+        let all_received = match &self.announced {
+            Some(version) => cfold.version() >= version,
+            None => false,
+        };
+        if all_received && !self.done_sent {
+            self.done_sent = true;
+            responses.push(encode(&Message::<A, T>::Done));
+        }
+        Ok(responses)
+    }
+
+    /// Returns `true` if both replicas received all ops they were missing.
+    pub fn is_complete(&self) -> bool {
+        self.done_sent && self.done_received
+    }
+}
+
+impl<A, T> Default for SyncSession<A, T>
+where
+    A: Author + Serialize + DeserializeOwned,
+    T: Clone + PartialEq + Serialize + DeserializeOwned,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn encode<A: Author + Serialize, T: Serialize>(message: &Message<A, T>) -> Vec<u8> {
+    serde_json::to_vec(message).expect("messages can always be serialized")
+}
+
+/// Represents errors that can occur during synchronization.
+#[derive(PartialEq, Eq, Clone)]
+pub enum SyncError<A, T> {
+    /// A message could not be decoded.
+    Malformed(String),
+    /// An op was rejected for reasons other than missing dependencies.
+    Apply(ChronofoldError<A, T>),
+    /// This many ops were waiting for their dependencies, more than allowed.
+    /// They were discarded.
+    TooManyPending(usize),
+}
+
+impl<A, T> From<BufferError<A, T>> for SyncError<A, T> {
+    fn from(err: BufferError<A, T>) -> Self {
+        match err {
+            BufferError::Apply(err) => SyncError::Apply(err),
+            BufferError::TooManyPending(count) => SyncError::TooManyPending(count),
+        }
+    }
+}
+
+impl<A, T> fmt::Debug for SyncError<A, T>
+where
+    A: fmt::Debug + fmt::Display + Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SyncError::Malformed(err) => f.debug_tuple("Malformed").field(err).finish(),
+            SyncError::Apply(err) => f.debug_tuple("Apply").field(err).finish(),
+            SyncError::TooManyPending(count) => {
+                f.debug_tuple("TooManyPending").field(count).finish()
+            }
+        }
+    }
+}
+
+impl<A, T> fmt::Display for SyncError<A, T>
+where
+    A: fmt::Debug + fmt::Display + Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SyncError::Malformed(err) => write!(f, "malformed message: {err}"),
+            SyncError::Apply(err) => write!(f, "rejected op: {err}"),
+            SyncError::TooManyPending(count) => {
+                write!(f, "{count} ops with missing dependencies")
+            }
+        }
+    }
+}
+
+impl<A, T> std::error::Error for SyncError<A, T> where A: fmt::Debug + fmt::Display + Copy {}
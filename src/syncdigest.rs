@@ -0,0 +1,215 @@
+//! Hash trees over the ops of each author.
+
+use std::collections::BTreeMap;
+use std::hash::Hash;
+use std::ops::Range;
+
+use crate::{Author, Chronofold, Digest, FromLocalValue, LendingStorage, LogIndex, Op, Storage};
+
+/// log2 of the number of timestamp indices covered by a leaf.
+const LEAF_BITS: u32 = 4;
+
+/// A node in the hash tree over an author's ops.
+///
+/// The tree is a binary tree over timestamp indices. Nodes at level 0 (the
+/// leaves) cover 16 timestamp indices, nodes at level `n` cover `16 * 2^n`.
+/// The `index` of a node is its position within its level, i.e. node `i`
+/// covers the `i`th range of that size.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DigestNode<A> {
+    pub author: A,
+    pub level: u32,
+    pub index: usize,
+}
+
+impl<A: Copy> DigestNode<A> {
+    /// Returns the range of timestamp indices covered by this node.
+    pub fn range(&self) -> Range<LogIndex> {
+        // Nodes might come from untrusted sources, so we have to avoid
+        // overflows here.
+        let size = 1u128 << (u32::min(self.level, 100) + LEAF_BITS);
+        let start = (self.index as u128).saturating_mul(size);
+        let end = (self.index as u128 + 1).saturating_mul(size);
+        let clamp = |i: u128| LogIndex(usize::try_from(i).unwrap_or(usize::MAX));
+        clamp(start)..clamp(end)
+    }
+
+    /// Returns `true` if this node has no children.
+    pub fn is_leaf(&self) -> bool {
+        self.level == 0
+    }
+
+    /// Returns the children of this node, which are empty for leaves.
+    pub fn children(&self) -> Vec<DigestNode<A>> {
+        if self.is_leaf() {
+            return vec![];
+        }
+        let first = self.index.saturating_mul(2);
+        [first, first.saturating_add(1)]
+            .iter()
+            .map(|index| DigestNode {
+                author: self.author,
+                level: self.level - 1,
+                index: *index,
+            })
+            .collect()
+    }
+}
+
+/// Hashes of nodes in the hash trees over the ops of a chronofold.
+///
+/// Replicas can use these to find ops missing on either side, even if their
+/// versions are equal (e.g. because a message was lost):
+///
+/// 1. One replica sends its `Chronofold::sync_digest` to the other one.
+/// 2. The receiver determines the differing nodes with
+///    `Chronofold::compare_sync_digest`. For differing leaves, both replicas
+///    exchange the ops covered by them (`Chronofold::iter_digest_node_ops`).
+///    For other nodes, the receiver replies with the hashes of their children
+///    (`Chronofold::sync_digest_of`), which is compared in the same way.
+///
+/// This takes a logarithmic number of round trips in the number of ops. Note
+/// that ops of different leaves might depend on each other, so they may have
+/// to be applied in causal order. Ops removed by `Chronofold::compact` are
+/// not covered by the hash trees.
+///
+/// ```rust
+/// use chronofold::{Chronofold, Op};
+///
+/// let mut cfold_a = Chronofold::<u8, char>::default();
+/// cfold_a.session(1).extend("Hello!".chars());
+/// let mut cfold_b = Chronofold::<u8, char>::default();
+///
+/// let mut digest = cfold_a.sync_digest();
+/// while !digest.is_empty() {
+///     let differing = cfold_b.compare_sync_digest(&digest);
+///     let (leaves, nodes): (Vec<_>, Vec<_>) =
+///         differing.into_iter().partition(|node| node.is_leaf());
+///     for leaf in leaves {
+///         let ops: Vec<Op<u8, char>> = cfold_a
+///             .iter_digest_node_ops(&leaf)
+///             .map(Op::cloned)
+///             .collect();
+///         for op in ops {
+///             cfold_b.apply_idempotent(op).unwrap();
+///         }
+///     }
+///     digest = cfold_a.sync_digest_of(nodes.iter().flat_map(|node| node.children()));
+/// }
+/// assert_eq!("Hello!", format!("{cfold_b}"));
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SyncDigest<A> {
+    nodes: Vec<(DigestNode<A>, Digest)>,
+}
+
+impl<A> SyncDigest<A> {
+    /// Returns `true` if the digest contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns an iterator over the nodes and their hashes.
+    pub fn iter(&self) -> impl Iterator<Item = &(DigestNode<A>, Digest)> {
+        self.nodes.iter()
+    }
+}
+
+impl<A: Author + Hash, T: Hash, S: Storage<A, T>> Chronofold<A, T, S> {
+    /// Returns the hashes of the roots of all authors' hash trees.
+    ///
+    /// See `SyncDigest` for how to use this.
+    pub fn sync_digest(&self) -> SyncDigest<A> {
+        let roots = self.version.iter().map(|t| {
+            let mut level = 0;
+            while (1 << (level + LEAF_BITS)) <= t.0 .0 {
+                level += 1;
+            }
+            DigestNode {
+                author: t.1,
+                level,
+                index: 0,
+            }
+        });
+        self.sync_digest_of(roots)
+    }
+
+    /// Returns the hashes of the given nodes.
+    pub fn sync_digest_of<I>(&self, nodes: I) -> SyncDigest<A>
+    where
+        I: IntoIterator<Item = DigestNode<A>>,
+    {
+        let prefix_sums = self.op_hash_prefix_sums();
+        SyncDigest {
+            nodes: nodes
+                .into_iter()
+                .map(|node| {
+                    let hash = match prefix_sums.get(&node.author) {
+                        Some(sums) => {
+                            let range = node.range();
+                            let sum_before =
+                                |idx: LogIndex| match sums.partition_point(|(i, _)| *i < idx) {
+                                    0 => 0,
+                                    n => sums[n - 1].1,
+                                };
+                            sum_before(range.end).wrapping_sub(sum_before(range.start))
+                        }
+                        None => 0,
+                    };
+                    (node, Digest(hash))
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the nodes of `digest` whose hashes differ from ours.
+    pub fn compare_sync_digest(&self, digest: &SyncDigest<A>) -> Vec<DigestNode<A>> {
+        let ours = self.sync_digest_of(digest.nodes.iter().map(|(node, _)| *node));
+        ours.nodes
+            .into_iter()
+            .zip(digest.nodes.iter())
+            .filter(|((_, ours), (_, theirs))| ours != theirs)
+            .map(|((node, _), _)| node)
+            .collect()
+    }
+
+    /// Returns, for each author, the timestamp indices of their ops in
+    /// ascending order, along with the wrapping sums of all op hashes up to
+    /// and including that op.
+    fn op_hash_prefix_sums(&self) -> BTreeMap<A, Vec<(LogIndex, u64)>> {
+        let mut hashes: BTreeMap<A, Vec<(LogIndex, u64)>> = BTreeMap::new();
+        for idx in (0..self.storage.len()).map(LogIndex) {
+            let id = self.timestamp(idx).unwrap();
+            hashes
+                .entry(id.1)
+                .or_default()
+                .push((id.0, self.op_hash(idx)));
+        }
+        for sums in hashes.values_mut() {
+            sums.sort_unstable();
+            let mut sum = 0u64;
+            for (_, hash) in sums.iter_mut() {
+                sum = sum.wrapping_add(*hash);
+                *hash = sum;
+            }
+        }
+        hashes
+    }
+}
+
+impl<A: Author, T, S: LendingStorage<A, T>> Chronofold<A, T, S> {
+    /// Returns an iterator over the ops covered by a node in log order.
+    pub fn iter_digest_node_ops<'a, V>(
+        &'a self,
+        node: &DigestNode<A>,
+    ) -> impl Iterator<Item = Op<A, V>> + 'a
+    where
+        V: FromLocalValue<'a, A, T, S> + 'a,
+    {
+        let (author, range) = (node.author, node.range());
+        self.iter_ops(..)
+            .filter(move |op| op.id.1 == author && range.contains(&op.id.0))
+    }
+}
@@ -0,0 +1,515 @@
+//! Utilities for testing code built on chronofolds.
+//!
+//! `Simulation` runs randomized edits on a number of replicas exchanging ops
+//! over a `Network` that delays, reorders and duplicates messages. Runs are
+//! fully determined by their seed, so failures can be reproduced.
+//!
+//! `ReferenceModel` is a naive implementation of the chronofold semantics,
+//! which `check_against_reference` compares a chronofold with.
+//!
+//! ```rust
+//! use chronofold::testing::{NetworkConfig, Simulation};
+//!
+//! let mut sim = Simulation::new(42, 3, NetworkConfig::default());
+//! sim.run(100);
+//! sim.settle();
+//! sim.check_convergence().unwrap();
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::mem;
+use std::ops::Range;
+
+use crate::{Author, CausalBuffer, Chronofold, LogIndex, Op, OpPayload, Timestamp};
+
+/// A small, seedable random number generator (SplitMix64).
+///
+/// Unlike the generators of the `rand` crate, its output is guaranteed to
+/// stay the same across versions of this crate.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn seed_from(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `range`, which must not be empty.
+    pub fn gen_range(&mut self, range: Range<usize>) -> usize {
+        assert!(!range.is_empty(), "empty range");
+        range.start + (self.next_u64() % (range.end - range.start) as u64) as usize
+    }
+
+    /// Returns `true` with probability `p`.
+    pub fn gen_bool(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64) < p * (1u64 << 53) as f64
+    }
+}
+
+/// Describes how unreliable a `Network` is.
+#[derive(Clone, Copy, Debug)]
+pub struct NetworkConfig {
+    /// The maximum number of ticks a message is delayed.
+    pub max_delay: usize,
+    /// The probability of a message being delivered twice.
+    pub duplicate: f64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            max_delay: 5,
+            duplicate: 0.1,
+        }
+    }
+}
+
+/// A simulated network between numbered nodes.
+///
+/// Messages get delayed by a random number of ticks and might be duplicated.
+/// Messages due at the same tick are delivered in random order. Messages are
+/// never lost.
+#[derive(Clone, Debug)]
+pub struct Network<M> {
+    config: NetworkConfig,
+    tick: usize,
+    in_flight: Vec<(usize, usize, M)>,
+}
+
+impl<M: Clone> Network<M> {
+    pub fn new(config: NetworkConfig) -> Self {
+        Self {
+            config,
+            tick: 0,
+            in_flight: vec![],
+        }
+    }
+
+    /// Sends a message to node `to`.
+    pub fn send(&mut self, rng: &mut Rng, to: usize, message: M) {
+        if rng.gen_bool(self.config.duplicate) {
+            self.enqueue(rng, to, message.clone());
+        }
+        self.enqueue(rng, to, message);
+    }
+
+    /// Advances the network by one tick and returns the messages delivered,
+    /// along with their recipients.
+    pub fn tick(&mut self, rng: &mut Rng) -> Vec<(usize, M)> {
+        self.tick += 1;
+        let (mut due, in_flight): (Vec<_>, Vec<_>) = mem::take(&mut self.in_flight)
+            .into_iter()
+            .partition(|(at, _, _)| *at <= self.tick);
+        self.in_flight = in_flight;
+        // Fisher-Yates shuffle
+        for i in (1..due.len()).rev() {
+            due.swap(i, rng.gen_range(0..i + 1));
+        }
+        due.into_iter().map(|(_, to, m)| (to, m)).collect()
+    }
+
+    /// Returns `true` if no messages are in flight.
+    pub fn is_idle(&self) -> bool {
+        self.in_flight.is_empty()
+    }
+
+    fn enqueue(&mut self, rng: &mut Rng, to: usize, message: M) {
+        let delay = rng.gen_range(1..self.config.max_delay + 2);
+        self.in_flight.push((self.tick + delay, to, message));
+    }
+}
+
+/// Randomized edits on replicas of a chronofold.
+///
+/// Every op is broadcast to all other replicas, which buffer ops until they
+/// can be applied. The edits are generated by an `Editor`, which is
+/// `RandomText` for simulations constructed with `Simulation::new`.
+#[derive(Clone, Debug)]
+pub struct Simulation<A = usize, T = char, E = RandomText> {
+    seed: u64,
+    rng: Rng,
+    network: Network<Op<A, T>>,
+    authors: Vec<A>,
+    replicas: Vec<Chronofold<A, T>>,
+    pending: Vec<CausalBuffer<A, T>>,
+    editor: E,
+}
+
+impl Simulation {
+    /// Constructs a simulation of `replicas` replicas editing text.
+    ///
+    /// Replica `i` authors its ops as `i + 1`, the root is authored by `0`.
+    pub fn new(seed: u64, replicas: usize, network: NetworkConfig) -> Self {
+        Self::with_replicas(
+            seed,
+            (1..=replicas).map(|author| (author, Chronofold::new(0))),
+            network,
+            RandomText,
+        )
+    }
+}
+
+impl<A, T, E> Simulation<A, T, E>
+where
+    A: Author,
+    T: Clone + PartialEq + fmt::Debug,
+    E: Editor<A, T>,
+{
+    /// Constructs a simulation of the given replicas, each of which edits as
+    /// the author it is paired with.
+    ///
+    /// The replicas should be equal, e.g. clones of the same chronofold.
+    pub fn with_replicas<I>(seed: u64, replicas: I, network: NetworkConfig, editor: E) -> Self
+    where
+        I: IntoIterator<Item = (A, Chronofold<A, T>)>,
+    {
+        let (authors, replicas): (Vec<_>, Vec<_>) = replicas.into_iter().unzip();
+        Self {
+            seed,
+            rng: Rng::seed_from(seed),
+            network: Network::new(network),
+            pending: vec![CausalBuffer::new(); replicas.len()],
+            authors,
+            replicas,
+            editor,
+        }
+    }
+
+    pub fn replicas(&self) -> &[Chronofold<A, T>] {
+        &self.replicas
+    }
+
+    /// Makes a random edit on a random replica, then advances the network by
+    /// one tick.
+    pub fn step(&mut self) {
+        let replica = self.rng.gen_range(0..self.replicas.len());
+        let ops = self.editor.edit(
+            &mut self.rng,
+            self.authors[replica],
+            &mut self.replicas[replica],
+        );
+        for op in ops {
+            for to in (0..self.replicas.len()).filter(|to| *to != replica) {
+                self.network.send(&mut self.rng, to, op.clone());
+            }
+        }
+        self.deliver();
+    }
+
+    /// Runs `steps` steps.
+    pub fn run(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    /// Advances the network until all messages are delivered.
+    pub fn settle(&mut self) {
+        while !self.network.is_idle() {
+            self.deliver();
+        }
+    }
+
+    /// Checks that all replicas contain the same elements as the reference
+    /// model and no ops are pending.
+    ///
+    /// This is only meaningful after `settle`.
+    pub fn check_convergence(&self) -> Result<(), ConvergenceError> {
+        let elements = |cfold: &Chronofold<A, T>| -> Vec<T> {
+            cfold.iter().map(|(value, _)| value.clone()).collect()
+        };
+        for (i, cfold) in self.replicas.iter().enumerate() {
+            let report = |problem: String| ConvergenceError {
+                report: format!(
+                    "seed {}: {}\n\nreplica {} ({:?}):\n{}",
+                    self.seed,
+                    problem,
+                    i,
+                    elements(cfold),
+                    cfold.formatted_log()
+                ),
+            };
+            if !self.pending[i].is_empty() {
+                return Err(report(format!(
+                    "replica {} has {} pending ops",
+                    i,
+                    self.pending[i].len()
+                )));
+            }
+            if let Err(err) = cfold.check_invariants() {
+                return Err(report(format!("replica {i} is invalid: {err}")));
+            }
+            let first = &self.replicas[0];
+            if i > 0 && (!cfold.content_eq(first) || cfold.version() != first.version()) {
+                let mut err = report(format!("replicas 0 and {i} diverged"));
+                err.report += &format!(
+                    "\nreplica 0 ({:?}):\n{}",
+                    elements(first),
+                    first.formatted_log()
+                );
+                return Err(err);
+            }
+        }
+        check_against_reference(&self.replicas[0]).map_err(|mut err| {
+            err.report = format!("seed {}: replica 0: {}", self.seed, err.report);
+            err
+        })
+    }
+
+    fn deliver(&mut self) {
+        for (to, op) in self.network.tick(&mut self.rng) {
+            if let Err(err) = self.pending[to].apply(&mut self.replicas[to], [op]) {
+                panic!("seed {}: replica {}: {}", self.seed, to, err)
+            }
+        }
+    }
+}
+
+/// Generates the edits of a `Simulation`.
+///
+/// This is implemented for closures taking the same arguments as `edit`.
+pub trait Editor<A, T> {
+    /// Edits `cfold` as `author` and returns the resulting ops.
+    fn edit(&mut self, rng: &mut Rng, author: A, cfold: &mut Chronofold<A, T>) -> Vec<Op<A, T>>;
+}
+
+impl<A, T, F> Editor<A, T> for F
+where
+    F: FnMut(&mut Rng, A, &mut Chronofold<A, T>) -> Vec<Op<A, T>>,
+{
+    fn edit(&mut self, rng: &mut Rng, author: A, cfold: &mut Chronofold<A, T>) -> Vec<Op<A, T>> {
+        self(rng, author, cfold)
+    }
+}
+
+/// Inserts a random word or deletes up to three random elements.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct RandomText;
+
+impl<A: Author> Editor<A, char> for RandomText {
+    fn edit(
+        &mut self,
+        rng: &mut Rng,
+        author: A,
+        cfold: &mut Chronofold<A, char>,
+    ) -> Vec<Op<A, char>> {
+        let current: Vec<LogIndex> = cfold.iter().map(|(_, idx)| idx).collect();
+        let mut session = cfold.session(author);
+        if current.is_empty() || rng.gen_bool(0.7) {
+            let word: String = (0..rng.gen_range(1..4))
+                .map(|_| (b'a' + rng.gen_range(0..26) as u8) as char)
+                .collect();
+            if current.is_empty() {
+                session.extend(word.chars());
+            } else {
+                let idx = current[rng.gen_range(0..current.len())];
+                session.splice(idx..idx, word.chars());
+            }
+        } else {
+            let length = usize::min(rng.gen_range(1..4), current.len());
+            let start = rng.gen_range(0..current.len() - length + 1);
+            session.splice(current[start]..=current[start + length - 1], "".chars());
+        }
+        session.iter_ops().map(Op::cloned).collect()
+    }
+}
+
+/// Replicas of a `Simulation` did not converge, or a chronofold differs from
+/// the reference model.
+///
+/// The report contains the simulation's seed, the texts of the replicas
+/// involved and their logs as returned by `Chronofold::formatted_log`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ConvergenceError {
+    pub report: String,
+}
+
+impl fmt::Debug for ConvergenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.report)
+    }
+}
+
+impl fmt::Display for ConvergenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.report)
+    }
+}
+
+impl std::error::Error for ConvergenceError {}
+
+/// A deliberately naive implementation of the chronofold semantics.
+///
+/// Ops form a tree, in which every op is a child of the op it references.
+/// Siblings are ordered by descending timestamps, so an insert is placed
+/// right after its reference, unless a concurrent insert with a greater
+/// timestamp referencing the same op exists. The weave is the depth-first
+/// traversal of this tree, the elements are the inserts in it that are not
+/// referenced by a delete.
+///
+/// Unlike `Chronofold`, this rebuilds the weave from all ops every time and
+/// does no validation at all. Ops whose reference is missing are ignored.
+#[derive(Clone, Debug)]
+pub struct ReferenceModel<A, T> {
+    ops: BTreeMap<Timestamp<A>, Op<A, T>>,
+}
+
+impl<A: Author, T> ReferenceModel<A, T> {
+    pub fn new() -> Self {
+        Self {
+            ops: BTreeMap::new(),
+        }
+    }
+
+    /// Adds an op. Ops with timestamps added before are ignored.
+    pub fn insert(&mut self, op: Op<A, T>) {
+        self.ops.entry(op.id).or_insert(op);
+    }
+
+    /// Returns the visible elements and their timestamps in order.
+    pub fn elements(&self) -> Vec<(Timestamp<A>, &T)> {
+        let mut children: BTreeMap<Timestamp<A>, Vec<Timestamp<A>>> = BTreeMap::new();
+        let mut deleted = BTreeSet::new();
+        let mut roots = vec![];
+        for op in self.ops.values() {
+            match &op.payload {
+                OpPayload::Root => roots.push(op.id),
+                OpPayload::Insert(Some(reference), _) => {
+                    children.entry(*reference).or_default().push(op.id)
+                }
+                OpPayload::Insert(None, _) => {}
+                OpPayload::Delete(reference) => {
+                    deleted.insert(*reference);
+                    children.entry(*reference).or_default().push(op.id)
+                }
+            }
+        }
+
+        // Children are in ascending order, so popping them from the stack
+        // visits them in descending order.
+        let mut elements = vec![];
+        let mut stack = roots;
+        while let Some(id) = stack.pop() {
+            if let OpPayload::Insert(_, value) = &self.ops[&id].payload {
+                if !deleted.contains(&id) {
+                    elements.push((id, value));
+                }
+            }
+            stack.extend(children.get(&id).into_iter().flatten());
+        }
+        elements
+    }
+}
+
+impl<A: Author, T> Default for ReferenceModel<A, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Checks that a chronofold contains the elements the reference model
+/// computes from its ops.
+pub fn check_against_reference<A, T>(cfold: &Chronofold<A, T>) -> Result<(), ConvergenceError>
+where
+    A: Author,
+    T: Clone + PartialEq + fmt::Debug,
+{
+    let mut model = ReferenceModel::new();
+    for op in cfold.iter_ops::<_, &T>(..) {
+        model.insert(op.cloned());
+    }
+    compare_with_model(cfold, &model)
+}
+
+/// Checks that a chronofold contains the elements of `model`.
+fn compare_with_model<A, T>(
+    cfold: &Chronofold<A, T>,
+    model: &ReferenceModel<A, T>,
+) -> Result<(), ConvergenceError>
+where
+    A: Author,
+    T: Clone + PartialEq + fmt::Debug,
+{
+    let expected = model.elements();
+    let actual: Vec<(Timestamp<A>, &T)> = cfold
+        .iter()
+        .map(|(value, idx)| (cfold.timestamp(idx).unwrap(), value))
+        .collect();
+    if actual == expected {
+        return Ok(());
+    }
+    let position = actual
+        .iter()
+        .zip(expected.iter())
+        .position(|(a, e)| a != e)
+        .unwrap_or(usize::min(actual.len(), expected.len()));
+    let format = |elements: &[(Timestamp<A>, &T)]| -> String {
+        elements
+            .iter()
+            .map(|(id, value)| format!("{id} {value:?}\n"))
+            .collect()
+    };
+    Err(ConvergenceError {
+        report: format!(
+            "differs from reference model at position {}\n\nexpected:\n{}\nactual:\n{}\n{}",
+            position,
+            format(&expected),
+            format(&actual),
+            cfold.formatted_log()
+        ),
+    })
+}
+
+/// Applies ops in the given order to a new chronofold and checks it against
+/// the reference model built from all of them.
+///
+/// Ops that can't be applied yet are buffered until their dependencies
+/// arrive. Fails if an op is rejected or still waiting for its dependencies
+/// in the end. Returns the resulting chronofold.
+pub fn check_ops_against_reference<A, T, I>(ops: I) -> Result<Chronofold<A, T>, ConvergenceError>
+where
+    A: Author,
+    T: Clone + PartialEq + fmt::Debug,
+    I: IntoIterator<Item = Op<A, T>>,
+{
+    let mut cfold = Chronofold::empty();
+    let mut model = ReferenceModel::new();
+    let mut buffer = CausalBuffer::new().with_max_pending(usize::MAX);
+    for op in ops {
+        model.insert(op.clone());
+        if let Err(err) = buffer.apply(&mut cfold, [op]) {
+            return Err(ConvergenceError {
+                report: format!(
+                    "{}
+
+{}",
+                    err,
+                    cfold.formatted_log()
+                ),
+            });
+        }
+    }
+    if let Some(op) = buffer.iter().next() {
+        return Err(ConvergenceError {
+            report: format!(
+                "{} ops with missing dependencies, e.g. {}
+
+{}",
+                buffer.len(),
+                op.id,
+                cfold.formatted_log()
+            ),
+        });
+    }
+    compare_with_model(&cfold, &model)?;
+    Ok(cfold)
+}
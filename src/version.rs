@@ -1,7 +1,7 @@
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
 
-use crate::{Author, Chronofold, FromLocalValue, LogIndex, Op, Timestamp};
+use crate::{Author, Chronofold, FromLocalValue, LendingStorage, LogIndex, Op, Storage, Timestamp};
 
 /// A vector clock representing the chronofold's version.
 #[derive(PartialEq, Eq, Clone, Debug)]
@@ -32,6 +32,18 @@ impl<A: Author> Version<A> {
     pub fn get(&self, author: &A) -> Option<LogIndex> {
         self.log_indices.get(author).cloned()
     }
+
+    /// Returns `true` if the version doesn't contain any timestamps.
+    pub fn is_empty(&self) -> bool {
+        self.log_indices.is_empty()
+    }
+
+    /// Returns `true` if `timestamp` is covered by this version.
+    pub fn contains(&self, timestamp: &Timestamp<A>) -> bool {
+        self.get(&timestamp.1)
+            .map(|idx| timestamp.0 <= idx)
+            .unwrap_or(false)
+    }
 }
 
 impl<A: Author> Default for Version<A> {
@@ -64,27 +76,41 @@ impl<A: Author> PartialOrd for Version<A> {
     }
 }
 
-impl<A: Author, T> Chronofold<A, T> {
+impl<A: Author, T, S: Storage<A, T>> Chronofold<A, T, S> {
     /// Returns a vector clock representing the version of this chronofold.
     pub fn version(&self) -> &Version<A> {
         &self.version
     }
 
+    /// Returns an iterator over ops newer than the given version with copies
+    /// of their values in log order.
+    ///
+    /// Unlike `iter_newer_ops`, this works with any storage.
+    pub fn iter_newer_cloned_ops<'a>(
+        &'a self,
+        version: &'a Version<A>,
+    ) -> impl Iterator<Item = Op<A, T>> + 'a
+    where
+        T: Clone,
+    {
+        self.iter_cloned_ops(..)
+            .filter(move |op| !version.contains(&op.id))
+    }
+}
+
+impl<A: Author, T, S: LendingStorage<A, T>> Chronofold<A, T, S> {
     /// Returns an iterator over ops newer than the given version in log order.
     pub fn iter_newer_ops<'a, V>(
         &'a self,
         version: &'a Version<A>,
     ) -> impl Iterator<Item = Op<A, V>> + 'a
     where
-        V: FromLocalValue<'a, A, T> + 'a,
+        V: FromLocalValue<'a, A, T, S> + 'a,
     {
         // TODO: Don't iterate over all ops in cases where that is not
         // necessary.
         self.iter_ops(..)
-            .filter(move |op| match version.log_indices.get(&op.id.1) {
-                None => true,
-                Some(idx) => op.id.0 > *idx,
-            })
+            .filter(move |op| !version.contains(&op.id))
     }
 }
 
@@ -123,3 +149,21 @@ mod serde {
         }
     }
 }
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary {
+    use super::Version;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use std::collections::BTreeMap;
+
+    impl<'a, A> Arbitrary<'a> for Version<A>
+    where
+        A: Arbitrary<'a> + Ord,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Self {
+                log_indices: BTreeMap::arbitrary(u)?,
+            })
+        }
+    }
+}
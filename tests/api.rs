@@ -145,7 +145,7 @@ where
     let mut vec: Vec<T> = initial_values.collect();
     let mut cfold = Chronofold::<u8, T>::default();
     let mut cfold_session = cfold.session(1);
-    cfold_session.extend(vec.clone().into_iter());
+    cfold_session.extend(vec.clone());
 
     mutate_vec(&mut vec);
     mutate_chronofold(&mut cfold_session);
@@ -0,0 +1,91 @@
+#![cfg(feature = "rkyv")]
+use chronofold::{ArchiveError, ArchivedChange, ArchivedChronofold, Chronofold, LogIndex, Op};
+
+fn text(archived: &ArchivedChronofold<u8, char>) -> String {
+    archived.iter().map(|(c, _)| c.to_native()).collect()
+}
+
+/// Returns a chronofold with concurrent edits and deletions.
+fn edited() -> Chronofold<u8, char> {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).extend("Hello world!".chars());
+    let mut other = cfold.clone();
+    other
+        .session(2)
+        .splice(LogIndex(7)..LogIndex(12), "Chronofold".chars());
+    cfold.session(1).insert_after(LogIndex(5), ',');
+    for op in other
+        .iter_ops(..)
+        .map(Op::cloned)
+        .collect::<Vec<Op<u8, char>>>()
+    {
+        let _ = cfold.apply(op);
+    }
+    cfold
+}
+
+#[test]
+fn read_in_place() {
+    let cfold = edited();
+    let bytes = cfold.to_archive();
+    let archived = ArchivedChronofold::<u8, char>::access(&bytes).unwrap();
+
+    assert_eq!(format!("{cfold}"), text(&archived));
+    assert_eq!(cfold.len(), archived.len());
+    assert_eq!(cfold.version(), &archived.version());
+    assert_eq!(
+        cfold.iter().map(|(_, idx)| idx).collect::<Vec<_>>(),
+        archived.iter().map(|(_, idx)| idx).collect::<Vec<_>>()
+    );
+    assert!(matches!(
+        archived.get(LogIndex(0)),
+        Some(ArchivedChange::Root)
+    ));
+    assert!(matches!(
+        archived.get(LogIndex(1)),
+        Some(ArchivedChange::Insert(c)) if *c == 'H'
+    ));
+    assert!(archived.get(LogIndex(100)).is_none());
+}
+
+#[test]
+fn roundtrip() {
+    let cfold = edited();
+    let bytes = cfold.to_archive();
+    let archived = ArchivedChronofold::<u8, char>::access(&bytes).unwrap();
+    let mut restored = archived.to_chronofold().unwrap();
+    assert_eq!(cfold, restored);
+
+    restored.session(3).push_back('?');
+    assert_eq!("Hello, Chronofold!?", format!("{restored}"));
+}
+
+#[test]
+fn compacted() {
+    let mut cfold = edited();
+    let stable = cfold.version().clone();
+    cfold.compact(&stable);
+    let bytes = cfold.to_archive();
+    let archived = ArchivedChronofold::<u8, char>::access(&bytes).unwrap();
+    assert_eq!(format!("{cfold}"), text(&archived));
+    assert_eq!(cfold, archived.to_chronofold().unwrap());
+}
+
+#[test]
+fn empty() {
+    let cfold = Chronofold::<u8, char>::empty();
+    let bytes = cfold.to_archive();
+    let archived = ArchivedChronofold::<u8, char>::access(&bytes).unwrap();
+    assert!(archived.is_empty());
+    assert_eq!(cfold, archived.to_chronofold().unwrap());
+}
+
+#[test]
+fn invalid_bytes() {
+    let bytes = Chronofold::<u8, char>::default().to_archive();
+    let truncated = &bytes[..bytes.len() - 4];
+    assert!(matches!(
+        ArchivedChronofold::<u8, char>::access(truncated),
+        Err(ArchiveError::Decode(_))
+    ));
+}
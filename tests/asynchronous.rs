@@ -0,0 +1,95 @@
+#![cfg(feature = "futures")]
+use futures::channel::mpsc;
+use futures::executor::block_on;
+use futures::{SinkExt, StreamExt};
+
+use chronofold::{
+    BufferError, Chronofold, ChronofoldError, LogIndex, Op, SharedChronofold, Timestamp,
+};
+
+fn ops(cfold: &Chronofold<u8, char>) -> Vec<Op<u8, char>> {
+    cfold.iter_ops(..).map(Op::cloned).collect()
+}
+
+#[test]
+fn buffers_ops_until_dependencies_arrive() {
+    let mut source = Chronofold::<u8, char>::default();
+    source.session(1).extend("abc".chars());
+    let target = SharedChronofold::new(Chronofold::<u8, char>::empty());
+
+    let mut sink = target.sink();
+    block_on(async {
+        for op in ops(&source).into_iter().rev() {
+            sink.send(op).await.unwrap();
+        }
+    });
+    assert_eq!("abc", format!("{}", *target.read()));
+
+    // Ops applied before are ignored.
+    block_on(sink.send(ops(&source).remove(1))).unwrap();
+    assert_eq!(source, *target.read());
+}
+
+#[test]
+fn rejects_equivocation() {
+    let target = SharedChronofold::new(Chronofold::<u8, char>::default());
+    target.edit(1, |session| session.push_back('a'));
+    let forged = Op::insert(
+        Timestamp(LogIndex(1), 1),
+        Some(Timestamp(LogIndex(0), 0)),
+        'b',
+    );
+    let result = block_on(target.sink().send(forged));
+    assert!(matches!(
+        result,
+        Err(BufferError::Apply(ChronofoldError::Equivocation { .. }))
+    ));
+}
+
+#[test]
+fn limits_pending_ops() {
+    let mut source = Chronofold::<u8, char>::default();
+    source.session(1).extend("abc".chars());
+    let target = SharedChronofold::new(Chronofold::<u8, char>::empty());
+
+    let mut sink = target.sink().with_max_pending(1);
+    let mut ops = ops(&source).into_iter().rev();
+    block_on(sink.send(ops.next().unwrap())).unwrap();
+    let result = block_on(sink.send(ops.next().unwrap()));
+    assert!(matches!(result, Err(BufferError::TooManyPending(2))));
+    assert!(target.read().is_empty());
+}
+
+#[test]
+fn forward_between_replicas() {
+    let alice = SharedChronofold::new(Chronofold::<u8, char>::default());
+    let bob = SharedChronofold::new(alice.read().clone());
+    let alice_ops = alice.local_ops();
+    let bob_ops = bob.local_ops();
+
+    alice.edit(1, |session| session.extend("Hello".chars()));
+    bob.edit(2, |session| session.extend("World".chars()));
+
+    // In-memory channels stand in for a network connection.
+    let (to_bob, from_alice) = mpsc::unbounded();
+    let (to_alice, from_bob) = mpsc::unbounded();
+    block_on(async {
+        alice_ops
+            .take(5)
+            .map(Ok)
+            .forward(to_bob.sink_map_err(|_| unreachable!()))
+            .await
+            .unwrap();
+        bob_ops
+            .take(5)
+            .map(Ok)
+            .forward(to_alice.sink_map_err(|_| unreachable!()))
+            .await
+            .unwrap();
+        from_alice.map(Ok).forward(bob.sink()).await.unwrap();
+        from_bob.map(Ok).forward(alice.sink()).await.unwrap();
+    });
+
+    assert_eq!(10, alice.read().len());
+    assert_eq!(format!("{}", *alice.read()), format!("{}", *bob.read()));
+}
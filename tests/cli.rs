@@ -0,0 +1,90 @@
+#![cfg(feature = "cli")]
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use chronofold::{Chronofold, LogIndex, Op};
+use tempfile::TempDir;
+
+fn write(dir: &TempDir, name: &str, json: String) -> PathBuf {
+    let path = dir.path().join(name);
+    std::fs::write(&path, json).unwrap();
+    path
+}
+
+fn run(args: &[&PathBuf], command: &str) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_chronofold"))
+        .arg(command)
+        .args(args)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    (output.status.success(), stdout + &stderr)
+}
+
+fn replicas(dir: &TempDir) -> (PathBuf, PathBuf) {
+    let mut cfold_a = Chronofold::<u64, char>::default();
+    cfold_a.session(1).extend("Hello\nworld\n".chars());
+    let mut cfold_b = cfold_a.clone();
+    cfold_b
+        .session(2)
+        .splice(LogIndex(7)..LogIndex(12), "there".chars());
+    let ops_b: Vec<Op<u64, char>> = cfold_b.iter_ops(..).map(Op::cloned).collect();
+    (
+        write(dir, "a.json", serde_json::to_string(&cfold_a).unwrap()),
+        write(dir, "b.json", serde_json::to_string(&ops_b).unwrap()),
+    )
+}
+
+#[test]
+fn inspects_files() {
+    let dir = TempDir::new().unwrap();
+    let (a, b) = replicas(&dir);
+    assert_eq!((true, "Hello\nthere\n\n".to_owned()), run(&[&b], "show"));
+    assert_eq!(
+        (true, "0\t0\n1\t12\n2\t22\n".to_owned()),
+        run(&[&b], "version")
+    );
+    assert_eq!(
+        (
+            true,
+            "1            | Hello\n1,2          | there\n".to_owned()
+        ),
+        run(&[&b], "blame")
+    );
+    assert_eq!(
+        (true, "ok: 12 elements, 13 ops\n".to_owned()),
+        run(&[&a], "check")
+    );
+    assert!(run(&[&a], "log")
+        .1
+        .starts_with("idx  | ref  | next | del  | change\n"));
+    assert_eq!(
+        (
+            true,
+            "--- a\n+++ b\n@@ -1,2 +1,2 @@\n Hello\n-world\n+there\n".to_owned()
+        ),
+        run(&[&a, &b], "diff")
+    );
+}
+
+#[test]
+fn merges_files() {
+    let dir = TempDir::new().unwrap();
+    let (a, b) = replicas(&dir);
+    let (success, json) = run(&[&a, &b], "merge");
+    assert!(success);
+    let merged: Chronofold<u64, char> = serde_json::from_str(&json).unwrap();
+    assert_eq!("Hello\nthere\n", format!("{merged}"));
+}
+
+#[test]
+fn reports_errors() {
+    let dir = TempDir::new().unwrap();
+    let invalid = write(&dir, "invalid.json", "[1, 2, 3]".to_owned());
+    let (success, output) = run(&[&invalid], "check");
+    assert!(!success);
+    assert!(output.starts_with("error: "));
+    assert!(!run(&[], "unknown").0);
+}
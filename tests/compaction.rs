@@ -0,0 +1,119 @@
+use chronofold::{Change, Chronofold, ChronofoldError, LogIndex, Op, Timestamp, Version};
+
+#[test]
+fn removes_deleted_elements() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).extend("Hello World!".chars());
+    cfold
+        .session(1)
+        .splice(LogIndex(7)..LogIndex(12), "Chronofold".chars());
+    cfold
+        .session(1)
+        .splice(LogIndex(2)..LogIndex(3), "a".chars());
+
+    let stable = cfold.version().clone();
+    cfold.compact(&stable);
+    assert_eq!("Hallo Chronofold!", format!("{cfold}"));
+    assert_eq!(None, cfold.get(LogIndex(18)));
+    assert_eq!(&stable, cfold.version());
+
+    cfold.session(1).extend("!!".chars());
+    assert_eq!("Hallo Chronofold!!!", format!("{cfold}"));
+}
+
+#[test]
+fn keeps_deletions_newer_than_stable() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).extend("abc".chars());
+    let stable = cfold.version().clone();
+    cfold
+        .session(1)
+        .splice(LogIndex(2)..LogIndex(3), "".chars());
+
+    cfold.compact(&stable);
+    assert_eq!("ac", format!("{cfold}"));
+    assert_eq!(Some(&Change::Delete), cfold.get(LogIndex(4)));
+}
+
+#[test]
+fn converges_with_uncompacted_replicas() {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    cfold_a.session(1).extend("0123456789".chars());
+    cfold_a
+        .session(1)
+        .splice(LogIndex(2)..LogIndex(8), "abc".chars());
+    let mut cfold_b = cfold_a.clone();
+    let stable = cfold_a.version().clone();
+    cfold_a.compact(&stable);
+
+    let ops_a: Vec<Op<u8, char>> = {
+        // Log indices change, so replace '0' by its timestamp.
+        let zero = cfold_a.log_index(&Timestamp(LogIndex(1), 1)).unwrap();
+        let mut session = cfold_a.session(1);
+        session.splice(zero..=zero, "x".chars());
+        session.push_back('y');
+        session.iter_ops().map(Op::cloned).collect()
+    };
+    let ops_b: Vec<Op<u8, char>> = {
+        let mut session = cfold_b.session(2);
+        session.splice(LogIndex(17)..LogIndex(19), "z".chars());
+        session.push_front('w');
+        session.iter_ops().map(Op::cloned).collect()
+    };
+    for op in ops_a {
+        cfold_b.apply(op).unwrap();
+    }
+    for op in ops_b {
+        cfold_a.apply(op).unwrap();
+    }
+
+    assert_eq!("wxzc789y", format!("{cfold_a}"));
+    assert_eq!(format!("{cfold_a}"), format!("{cfold_b}"));
+    assert_eq!(cfold_a.version(), cfold_b.version());
+}
+
+#[test]
+fn removed_ops_are_known() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).extend("ab".chars());
+    let removed: Op<u8, char> = cfold
+        .iter_ops(LogIndex(2)..LogIndex(3))
+        .next()
+        .unwrap()
+        .cloned();
+    cfold.session(1).clear();
+    let stable = cfold.version().clone();
+    cfold.compact(&stable);
+
+    assert_eq!(
+        Err(ChronofoldError::ExistingTimestamp(removed.clone())),
+        cfold.apply(removed.clone())
+    );
+    assert_eq!(None, cfold.log_index(&removed.id));
+
+    let op = Op::insert(Timestamp(LogIndex(5), 2), Some(removed.id), 'c');
+    assert_eq!(
+        Err(ChronofoldError::UnknownReference(op.clone())),
+        cfold.apply(op)
+    );
+}
+
+#[test]
+fn compacting_unseen_versions() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).extend("ab".chars());
+    cfold.session(1).clear();
+    let mut stable = Version::default();
+    stable.inc(&Timestamp(LogIndex(100), 1));
+    stable.inc(&Timestamp(LogIndex(100), 2));
+    cfold.compact(&stable);
+    assert_eq!("", format!("{cfold}"));
+
+    let op = Op::insert(
+        Timestamp(LogIndex(5), 2),
+        Some(Timestamp(LogIndex(0), 0)),
+        'c',
+    );
+    assert!(cfold.apply(op).is_ok());
+    assert_eq!("c", format!("{cfold}"));
+}
@@ -0,0 +1,50 @@
+use chronofold::{Chronofold, LogIndex, Op};
+
+#[test]
+fn independent_of_log_order() {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    cfold_a.session(1).extend("abc".chars());
+    let mut cfold_b = cfold_a.clone();
+    let ops_a: Vec<Op<u8, char>> = {
+        let mut session = cfold_a.session(1);
+        session.splice(LogIndex(2)..LogIndex(3), "x".chars());
+        session.iter_ops().map(Op::cloned).collect()
+    };
+    let ops_b: Vec<Op<u8, char>> = {
+        let mut session = cfold_b.session(2);
+        session.push_back('d');
+        session.iter_ops().map(Op::cloned).collect()
+    };
+    assert_ne!(cfold_a.digest(), cfold_b.digest());
+
+    for op in ops_a {
+        cfold_b.apply(op).unwrap();
+    }
+    for op in ops_b {
+        cfold_a.apply(op).unwrap();
+    }
+    assert_ne!(cfold_a, cfold_b);
+    assert_eq!(cfold_a.digest(), cfold_b.digest());
+}
+
+#[test]
+fn stable_across_platforms() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).extend("abc".chars());
+    assert_eq!("c78e2e4ea4b40c1b", format!("{}", cfold.digest()));
+}
+
+#[test]
+fn content_eq() {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    cfold_a.session(1).extend("abc".chars());
+    let mut cfold_b = Chronofold::<u8, char>::default();
+    cfold_b.session(2).extend("axc".chars());
+    assert!(!cfold_a.content_eq(&cfold_b));
+
+    cfold_b
+        .session(2)
+        .splice(LogIndex(2)..LogIndex(3), "b".chars());
+    assert!(cfold_a.content_eq(&cfold_b));
+    assert_ne!(cfold_a.digest(), cfold_b.digest());
+}
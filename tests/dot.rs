@@ -0,0 +1,26 @@
+use chronofold::{Chronofold, LogIndex};
+
+#[test]
+fn renders_tree_and_weave() {
+    let mut cfold = Chronofold::<u8, char>::new(0);
+    cfold.session(1).extend("a\"".chars());
+    cfold.session(2).remove(LogIndex(1));
+    assert_eq!(
+        concat!(
+            "digraph chronofold {\n",
+            "    node [style=filled];\n",
+            "    0 [label=\"0\\n<0, 0>\\nRoot\", fillcolor=\"#8dd3c7\"];\n",
+            "    1 [label=\"1\\n<1, 1>\\nInsert('a')\", fillcolor=\"#ffffb3\", style=\"filled,dashed\", fontcolor=gray];\n",
+            "    2 [label=\"2\\n<2, 1>\\nInsert('\\\"')\", fillcolor=\"#ffffb3\"];\n",
+            "    3 [label=\"3\\n<3, 2>\\nDelete\", fillcolor=\"#bebada\", shape=box];\n",
+            "    0 -> 1;\n",
+            "    1 -> 2;\n",
+            "    1 -> 3;\n",
+            "    0 -> 1 [style=dashed, color=blue, constraint=false];\n",
+            "    1 -> 3 [style=dashed, color=blue, constraint=false];\n",
+            "    3 -> 2 [style=dashed, color=blue, constraint=false];\n",
+            "}\n",
+        ),
+        cfold.to_dot()
+    );
+}
@@ -0,0 +1,40 @@
+use chronofold::{Chronofold, LogIndex, Op, Timestamp};
+
+#[test]
+fn insertions_and_deletions() {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    cfold_a.session(1).extend("ac".chars());
+    let mut cfold_b = cfold_a.clone();
+    let ops: Vec<Op<u8, char>> = {
+        let mut session = cfold_a.session(1);
+        session.insert_after(LogIndex(1), 'b');
+        session.remove(LogIndex(1));
+        session.iter_ops().map(Op::cloned).collect()
+    };
+
+    let insertion = cfold_b.apply(ops[0].clone()).unwrap();
+    assert_eq!(LogIndex(3), insertion.index());
+    assert!(insertion.changed());
+    assert_eq!(Some(1), insertion.position(&cfold_b));
+    let deletion = cfold_b.apply(ops[1].clone()).unwrap();
+    assert_eq!(LogIndex(4), deletion.index());
+    assert!(deletion.changed());
+    assert_eq!(Some(0), deletion.position(&cfold_b));
+    assert_eq!("bc", format!("{cfold_b}"));
+}
+
+#[test]
+fn concurrent_deletions() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).push_back('a');
+    let a = Timestamp(LogIndex(1), 1);
+    cfold
+        .apply(Op::<u8, char>::delete(Timestamp(LogIndex(2), 1), a))
+        .unwrap();
+
+    let effect = cfold
+        .apply(Op::<u8, char>::delete(Timestamp(LogIndex(2), 2), a))
+        .unwrap();
+    assert_eq!(None, effect.position(&cfold));
+    assert!(!effect.changed());
+}
@@ -1,4 +1,4 @@
-use chronofold::{Chronofold, ChronofoldError, LogIndex, Op, Timestamp};
+use chronofold::{ApplyOutcome, Chronofold, ChronofoldError, LogIndex, Op, Storage, Timestamp};
 
 #[test]
 fn unknown_timestamp() {
@@ -33,8 +33,175 @@ fn existing_timestamp() {
         Some(Timestamp(LogIndex(0), 0)),
         '.',
     );
-    assert_eq!(Ok(()), cfold.apply(op.clone()));
+    assert!(cfold.apply(op.clone()).is_ok());
     let err = cfold.apply(op.clone()).unwrap_err();
     assert_eq!(ChronofoldError::ExistingTimestamp(op), err);
     assert_eq!("existing timestamp <1, 1>", format!("{err}"));
 }
+
+#[test]
+fn insert_without_reference() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    let op = Op::insert(Timestamp(LogIndex(1), 1), None, '.');
+    let err = cfold.apply(op.clone()).unwrap_err();
+    assert_eq!(ChronofoldError::InvalidReferenceKind(op), err);
+    assert_eq!("missing reference in <1, 1>", format!("{err}"));
+}
+
+#[test]
+fn delete_of_delete() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).push_back('.');
+    cfold.session(1).remove(LogIndex(1));
+    let op: Op<u8, char> = Op::delete(Timestamp(LogIndex(3), 2), Timestamp(LogIndex(2), 1));
+    let err = cfold.apply(op.clone()).unwrap_err();
+    assert_eq!(ChronofoldError::InvalidReferenceKind(op), err);
+    assert_eq!("invalid reference <2, 1> in <3, 2>", format!("{err}"));
+}
+
+#[test]
+fn delete_of_root() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    let op: Op<u8, char> = Op::delete(Timestamp(LogIndex(1), 1), Timestamp(LogIndex(0), 0));
+    let err = cfold.apply(op.clone()).unwrap_err();
+    assert_eq!(ChronofoldError::DeleteOfRoot(op), err);
+    assert_eq!("deletion of root in <1, 1>", format!("{err}"));
+}
+
+#[test]
+fn malformed_root() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    let op: Op<u8, char> = Op::root(Timestamp(LogIndex(1), 1));
+    let err = cfold.apply(op.clone()).unwrap_err();
+    assert_eq!(ChronofoldError::MalformedRoot(op), err);
+    assert_eq!("root <1, 1> in non-empty chronofold", format!("{err}"));
+
+    let mut cfold = Chronofold::<u8, char>::empty();
+    assert!(cfold
+        .apply(Op::<u8, char>::root(Timestamp(LogIndex(0), 1)))
+        .is_ok());
+}
+
+#[test]
+fn session_roots_and_inserts() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    let err = cfold.session(1).create_root().unwrap_err();
+    assert_eq!(
+        ChronofoldError::MalformedRoot(Op::root(Timestamp(LogIndex(1), 1))),
+        err
+    );
+    assert_eq!(1, cfold.storage().len());
+
+    // Inserts are anchored, so other replicas accept them.
+    let mut replica = cfold.clone();
+    let ops: Vec<Op<u8, char>> = {
+        let mut session = cfold.session(1);
+        session.push_back('b');
+        session.insert('a');
+        session.iter_ops().map(Op::cloned).collect()
+    };
+    for op in ops {
+        replica.apply(op).unwrap();
+    }
+    assert_eq!("ab", format!("{replica}"));
+    assert_eq!(Ok(()), replica.check_invariants());
+}
+
+#[test]
+#[should_panic(expected = "chronofold has no root")]
+fn edit_without_root() {
+    let mut cfold = Chronofold::<u8, char>::empty();
+    assert_eq!(Ok(()), cfold.check_invariants());
+    cfold.session(1).extend("a".chars());
+}
+
+#[test]
+fn equivocation() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    let root = Some(Timestamp(LogIndex(0), 0));
+    let op = Op::insert(Timestamp(LogIndex(1), 1), root, '.');
+    let conflicting = Op::insert(Timestamp(LogIndex(1), 1), root, '!');
+    assert!(cfold.apply(op.clone()).is_ok());
+    assert_eq!(
+        Err(ChronofoldError::ExistingTimestamp(conflicting.clone())),
+        cfold.apply(conflicting.clone())
+    );
+    assert_eq!(
+        Err(ChronofoldError::ExistingTimestamp(op.clone())),
+        cfold.apply_checked(op.clone())
+    );
+    let err = cfold.apply_checked(conflicting.clone()).unwrap_err();
+    assert_eq!(
+        ChronofoldError::Equivocation {
+            existing: op,
+            received: conflicting
+        },
+        err
+    );
+    assert_eq!("equivocation at <1, 1>", format!("{err}"));
+    assert_eq!(".", format!("{cfold}"));
+}
+
+#[test]
+fn apply_converted_values() {
+    let mut cfold = Chronofold::<u8, String>::default();
+    let root = Some(Timestamp(LogIndex(0), 0));
+    let op = Op::insert(Timestamp(LogIndex(1), 1), root, "a");
+    assert!(cfold.apply(op).is_ok());
+
+    // `apply_checked` converts the existing value back, which `&str` can't
+    // be converted from.
+    let op = Op::insert(Timestamp(LogIndex(1), 1), root, Box::<str>::from("a"));
+    assert_eq!(
+        Err(ChronofoldError::ExistingTimestamp(op.clone())),
+        cfold.apply_checked(op.clone())
+    );
+    let conflicting = Op::insert(Timestamp(LogIndex(1), 1), root, Box::<str>::from("b"));
+    assert_eq!(
+        Err(ChronofoldError::Equivocation {
+            existing: op,
+            received: conflicting.clone()
+        }),
+        cfold.apply_checked(conflicting)
+    );
+}
+
+#[test]
+fn apply_idempotent() {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    let mut cfold_b = cfold_a.clone();
+    let ops: Vec<Op<u8, char>> = {
+        let mut session = cfold_a.session(1);
+        session.extend("ab".chars());
+        session.iter_ops().map(Op::cloned).collect()
+    };
+
+    for op in ops.iter().chain(ops.iter()).cloned() {
+        cfold_b.apply_idempotent(op).unwrap();
+    }
+    assert_eq!("ab", format!("{cfold_b}"));
+    assert_eq!(
+        Ok(ApplyOutcome::AlreadyKnown),
+        cfold_b.apply_idempotent(ops[0].clone())
+    );
+
+    let mut cfold_c = Chronofold::<u8, char>::default();
+    assert!(matches!(
+        cfold_c.apply_idempotent(ops[0].clone()),
+        Ok(ApplyOutcome::Applied(effect)) if effect.index() == LogIndex(1) && effect.changed()
+    ));
+    let err = cfold_c
+        .apply_idempotent(Op::insert(
+            ops[0].id,
+            ops[0].payload.reference().copied(),
+            'x',
+        ))
+        .unwrap_err();
+    assert_eq!(
+        ChronofoldError::Equivocation {
+            existing: ops[0].clone(),
+            received: Op::insert(ops[0].id, ops[0].payload.reference().copied(), 'x'),
+        },
+        err
+    );
+}
@@ -0,0 +1,109 @@
+use chronofold::{Chronofold, ChronofoldError, ClientId, Hub, HubError, LogIndex, Op, Version};
+
+struct Client {
+    id: ClientId,
+    cfold: Chronofold<u8, char>,
+    outbox: Vec<Op<u8, char>>,
+}
+
+impl Client {
+    fn connect(hub: &mut Hub<u8, char>) -> Self {
+        let cfold = hub.chronofold().clone();
+        Client {
+            id: hub.connect(cfold.version().clone()),
+            cfold,
+            outbox: vec![],
+        }
+    }
+
+    fn edit<F, R>(&mut self, author: u8, mutate: F)
+    where
+        F: FnOnce(&mut chronofold::Session<u8, char>) -> R,
+    {
+        let mut session = self.cfold.session(author);
+        mutate(&mut session);
+        self.outbox.extend(session.iter_ops().map(Op::cloned));
+    }
+
+    fn push(&mut self, hub: &mut Hub<u8, char>) {
+        hub.receive(self.id, self.outbox.drain(..)).unwrap();
+    }
+
+    fn pull(&mut self, hub: &mut Hub<u8, char>) {
+        for op in hub.missing_ops(self.id).unwrap() {
+            self.cfold.apply_idempotent(op).unwrap();
+        }
+        hub.acknowledge(self.id, self.cfold.version()).unwrap();
+    }
+}
+
+#[test]
+fn fans_out_ops() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(0).extend("Hello".chars());
+    let mut hub = Hub::new(cfold);
+    let mut clients: Vec<Client> = (0..3).map(|_| Client::connect(&mut hub)).collect();
+
+    clients[0].edit(1, |s| s.extend(" world".chars()));
+    clients[1].edit(2, |s| {
+        s.remove(LogIndex(1));
+        s.insert_after(LogIndex(0), 'h');
+    });
+    clients[2].edit(3, |s| s.extend("!".chars()));
+    for client in clients.iter_mut() {
+        client.push(&mut hub);
+    }
+    for client in clients.iter_mut() {
+        client.pull(&mut hub);
+    }
+
+    for client in clients.iter() {
+        assert_eq!(format!("{}", hub.chronofold()), format!("{}", client.cfold));
+        assert_eq!(hub.chronofold().version(), client.cfold.version());
+        assert!(hub.missing_ops(client.id).unwrap().is_empty());
+    }
+}
+
+#[test]
+fn skips_own_ops() {
+    let mut hub = Hub::new(Chronofold::<u8, char>::default());
+    let mut alice = Client::connect(&mut hub);
+    alice.edit(1, |s| s.extend("abc".chars()));
+    alice.push(&mut hub);
+    assert!(hub.missing_ops(alice.id).unwrap().is_empty());
+}
+
+#[test]
+fn catches_up_late_clients() {
+    let mut hub = Hub::new(Chronofold::<u8, char>::default());
+    let mut alice = Client::connect(&mut hub);
+    alice.edit(1, |s| s.extend("abc".chars()));
+    alice.push(&mut hub);
+
+    let bob = hub.connect(Version::new());
+    assert_eq!(4, hub.missing_ops(bob).unwrap().len());
+}
+
+#[test]
+fn reports_errors() {
+    let mut hub = Hub::new(Chronofold::<u8, char>::default());
+    let client = hub.connect(Version::new());
+    hub.disconnect(client).unwrap();
+    assert_eq!(
+        Err(HubError::UnknownClient(client)),
+        hub.missing_ops(client)
+    );
+
+    let client = hub.connect(Version::new());
+    let op = Op::insert(
+        chronofold::Timestamp(LogIndex(5), 1),
+        Some(chronofold::Timestamp(LogIndex(3), 1)),
+        'x',
+    );
+    assert_eq!(
+        Err(HubError::Apply(ChronofoldError::FutureTimestamp(
+            op.clone()
+        ))),
+        hub.receive(client, vec![op])
+    );
+}
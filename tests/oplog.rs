@@ -0,0 +1,195 @@
+#![cfg(feature = "oplog")]
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chronofold::{Chronofold, LogIndex, Op, OpLog, OpLogError};
+use tempfile::TempDir;
+
+/// Creates a log containing a root and "Hello", returning the log's path.
+fn hello(dir: &TempDir) -> PathBuf {
+    let path = dir.path().join("log");
+    let (mut log, _) = OpLog::<u8, char>::open(&path).unwrap();
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).extend("Hello".chars());
+    for op in cfold.iter_ops(..).map(Op::cloned) {
+        log.append(&op).unwrap();
+    }
+    log.sync().unwrap();
+    path
+}
+
+#[test]
+fn restores_local_and_remote_ops() {
+    let dir = TempDir::new().unwrap();
+    let path = hello(&dir);
+
+    let (mut log, mut cfold) = OpLog::<u8, char>::open(&path).unwrap();
+    let remote: Op<u8, char> = {
+        let mut other = cfold.clone();
+        let mut session = other.session(2);
+        session.insert_after(LogIndex(5), '!');
+        let op = session.iter_ops().map(Op::cloned).next().unwrap();
+        op
+    };
+    cfold.apply(remote.clone()).unwrap();
+    log.append(&remote).unwrap();
+    {
+        let mut session = cfold.session(1);
+        session.remove(LogIndex(1));
+        for op in session.iter_ops::<&char>() {
+            log.append(&op).unwrap();
+        }
+    }
+    drop(log);
+
+    let (_, restored) = OpLog::<u8, char>::open(&path).unwrap();
+    assert_eq!("ello!", format!("{restored}"));
+    assert_eq!(cfold, restored);
+}
+
+/// Appends "!" to the log at `path`.
+fn append_bang(path: &Path) {
+    let (mut log, mut cfold) = OpLog::<u8, char>::open(path).unwrap();
+    let mut session = cfold.session(1);
+    session.push_back('!');
+    for op in session.iter_ops::<&char>() {
+        log.append(&op).unwrap();
+    }
+}
+
+#[test]
+fn truncates_torn_records() {
+    let dir = TempDir::new().unwrap();
+    let path = hello(&dir);
+    let len = fs::metadata(&path).unwrap().len();
+
+    // A record whose payload was only partially written.
+    append_bang(&path);
+    let torn_len = fs::metadata(&path).unwrap().len() - 2;
+    OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_len(torn_len)
+        .unwrap();
+
+    let (log, cfold) = OpLog::<u8, char>::open(&path).unwrap();
+    assert_eq!("Hello", format!("{cfold}"));
+    assert_eq!(len, fs::metadata(&path).unwrap().len());
+    drop(log);
+
+    append_bang(&path);
+    let (_, restored) = OpLog::<u8, char>::open(&path).unwrap();
+    assert_eq!("Hello!", format!("{restored}"));
+}
+
+#[test]
+fn truncates_trailing_zeros() {
+    let dir = TempDir::new().unwrap();
+    let path = hello(&dir);
+    let len = fs::metadata(&path).unwrap().len();
+
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&[0; 64]).unwrap();
+    drop(file);
+
+    let (_, cfold) = OpLog::<u8, char>::open(&path).unwrap();
+    assert_eq!("Hello", format!("{cfold}"));
+    assert_eq!(len, fs::metadata(&path).unwrap().len());
+}
+
+#[test]
+fn rejects_invalid_headers_of_last_records() {
+    let dir = TempDir::new().unwrap();
+    let path = hello(&dir);
+    let len = fs::metadata(&path).unwrap().len();
+
+    // Without a valid header, a cut off record can't be told from a corrupt
+    // one.
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&[
+        100, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, b'{',
+    ])
+    .unwrap();
+    drop(file);
+    let bytes = fs::read(&path).unwrap();
+
+    assert!(matches!(
+        OpLog::<u8, char>::open(&path),
+        Err(OpLogError::Corrupt(offset)) if offset == len
+    ));
+    assert_eq!(bytes, fs::read(&path).unwrap());
+}
+
+#[test]
+fn rejects_corrupt_records() {
+    let dir = TempDir::new().unwrap();
+    let path = hello(&dir);
+    let mut bytes = fs::read(&path).unwrap();
+    bytes[20] ^= 0xff;
+    fs::write(&path, bytes).unwrap();
+
+    assert!(matches!(
+        OpLog::<u8, char>::open(&path),
+        Err(OpLogError::Corrupt(0))
+    ));
+}
+
+#[test]
+fn rejects_complete_records_with_invalid_checksums() {
+    let dir = TempDir::new().unwrap();
+    let path = hello(&dir);
+    let mut bytes = fs::read(&path).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    fs::write(&path, &bytes).unwrap();
+
+    assert!(matches!(
+        OpLog::<u8, char>::open(&path),
+        Err(OpLogError::Corrupt(_))
+    ));
+    assert_eq!(bytes, fs::read(&path).unwrap());
+}
+
+#[test]
+fn rejects_invalid_lengths_in_the_middle() {
+    let dir = TempDir::new().unwrap();
+    let path = hello(&dir);
+    let mut bytes = fs::read(&path).unwrap();
+
+    // A length pointing past the end of the file must not truncate the
+    // records after it. Records start with a 20 byte header.
+    let first_len = u32::from_le_bytes(bytes[..4].try_into().unwrap()) as usize;
+    let second = 20 + first_len;
+    bytes[second + 3] = 0xff;
+    fs::write(&path, &bytes).unwrap();
+
+    assert!(matches!(
+        OpLog::<u8, char>::open(&path),
+        Err(OpLogError::Corrupt(offset)) if offset == second as u64
+    ));
+    assert_eq!(bytes, fs::read(&path).unwrap());
+}
+
+#[test]
+fn loads_snapshots() {
+    let dir = TempDir::new().unwrap();
+    let path = hello(&dir);
+
+    let (mut log, mut cfold) = OpLog::<u8, char>::open(&path).unwrap();
+    log.snapshot(&cfold).unwrap();
+    assert_eq!(0, fs::metadata(&path).unwrap().len());
+
+    let mut session = cfold.session(1);
+    session.push_back('!');
+    for op in session.iter_ops::<&char>() {
+        log.append(&op).unwrap();
+    }
+    drop(log);
+
+    let (_, restored) = OpLog::<u8, char>::open(&path).unwrap();
+    assert_eq!("Hello!", format!("{restored}"));
+    assert_eq!(cfold, restored);
+}
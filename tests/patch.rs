@@ -0,0 +1,108 @@
+use chronofold::{Chronofold, LogIndex, Op, PatchError};
+
+#[test]
+fn unified_diff() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).extend("foo\nbar\nbaz\n".chars());
+    let v1 = cfold.version().clone();
+    cfold
+        .session(1)
+        .splice(LogIndex(5)..LogIndex(8), "qux".chars());
+    let v2 = cfold.version().clone();
+
+    assert_eq!(
+        "--- a\n+++ b\n@@ -1,3 +1,3 @@\n foo\n-bar\n+qux\n baz\n",
+        cfold.to_unified_diff(&v1, &v2)
+    );
+    assert_eq!("", cfold.to_unified_diff(&v2, &v2));
+}
+
+#[test]
+fn unified_diff_without_trailing_newline() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    let v1 = cfold.version().clone();
+    cfold.session(1).extend("foo".chars());
+    let v2 = cfold.version().clone();
+
+    assert_eq!(
+        "--- a\n+++ b\n@@ -0,0 +1,1 @@\n+foo\n\\ No newline at end of file\n",
+        cfold.to_unified_diff(&v1, &v2)
+    );
+}
+
+#[test]
+fn apply_patch_roundtrip() {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    cfold_a
+        .session(1)
+        .extend("one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten".chars());
+    let mut cfold_b = cfold_a.clone();
+    let v1 = cfold_a.version().clone();
+    {
+        let mut session = cfold_a.session(1);
+        session.splice(LogIndex(5)..LogIndex(8), "2".chars());
+        session.extend("\neleven".chars());
+    }
+    let patch = cfold_a.to_unified_diff(&v1, cfold_a.version());
+
+    cfold_b.session(2).apply_patch(&patch).unwrap();
+    assert_eq!(format!("{cfold_a}"), format!("{cfold_b}"));
+}
+
+#[test]
+fn apply_patch_roundtrip_crlf() {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    cfold_a.session(1).extend("one\r\ntwo\r\nthree\r\n".chars());
+    let mut cfold_b = cfold_a.clone();
+    let v1 = cfold_a.version().clone();
+    cfold_a
+        .session(1)
+        .splice(LogIndex(6)..LogIndex(9), "2".chars());
+    let patch = cfold_a.to_unified_diff(&v1, cfold_a.version());
+    assert!(patch.contains("-two\r\n+2\r\n"));
+
+    cfold_b.session(2).apply_patch(&patch).unwrap();
+    assert_eq!("one\r\n2\r\nthree\r\n", format!("{cfold_b}"));
+}
+
+#[test]
+fn apply_patch_ops_converge() {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    cfold_a.session(1).extend("a\nb\nc\n".chars());
+    let mut cfold_b = cfold_a.clone();
+
+    let ops: Vec<Op<u8, char>> = {
+        let mut session = cfold_b.session(2);
+        session
+            .apply_patch("@@ -2,1 +2,2 @@\n-b\n+x\n+y\n")
+            .unwrap();
+        session.iter_ops().map(Op::cloned).collect()
+    };
+    for op in ops {
+        cfold_a.apply(op).unwrap();
+    }
+    assert_eq!("a\nx\ny\nc\n", format!("{cfold_a}"));
+    assert_eq!("a\nx\ny\nc\n", format!("{cfold_b}"));
+}
+
+#[test]
+fn rejected_hunks() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).extend("a\nb\nc\n".chars());
+    let patch = "@@ -1,1 +1,1 @@\n-a\n+A\n@@ -3,1 +3,1 @@\n-x\n+X\n";
+
+    let err = cfold.session(1).apply_patch(patch).unwrap_err();
+    assert_eq!(PatchError::Rejected(vec![2]), err);
+    assert_eq!("hunks #2 failed to apply", format!("{err}"));
+    assert_eq!("a\nb\nc\n", format!("{cfold}"));
+}
+
+#[test]
+fn malformed_patch() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    let patch = "--- a\n+++ b\n@@ -1,2 +1,1 @@\n-a\n";
+    assert_eq!(
+        Err(PatchError::Malformed(3)),
+        cfold.session(1).apply_patch(patch)
+    );
+}
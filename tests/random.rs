@@ -31,6 +31,8 @@ fn random_edits_by_two_authors() {
         }
     }
     assert_eq!(format!("{cfold_alice}"), format!("{cfold_bob}"));
+    assert_eq!(Ok(()), cfold_alice.check_invariants());
+    assert_eq!(Ok(()), cfold_bob.check_invariants());
 }
 
 fn random_edits(
@@ -0,0 +1,68 @@
+#![cfg(feature = "testing")]
+
+use chronofold::testing::{
+    check_against_reference, check_ops_against_reference, NetworkConfig, ReferenceModel, Rng,
+    Simulation,
+};
+use chronofold::{Chronofold, LogIndex, Op, Timestamp};
+
+#[test]
+fn weaves_concurrent_inserts() {
+    let t = |idx, author| Timestamp(LogIndex(idx), author);
+    let mut model = ReferenceModel::new();
+    model.insert(Op::root(t(0, 0)));
+    model.insert(Op::insert(t(1, 1), Some(t(0, 0)), 'a'));
+    model.insert(Op::insert(t(2, 1), Some(t(1, 1)), 'b'));
+    model.insert(Op::insert(t(2, 2), Some(t(1, 1)), 'c'));
+    model.insert(Op::insert(t(3, 2), Some(t(2, 2)), 'd'));
+    model.insert(Op::delete(t(3, 1), t(2, 1)));
+    model.insert(Op::insert(t(9, 3), Some(t(8, 3)), 'x'));
+    let elements: String = model.elements().into_iter().map(|(_, c)| *c).collect();
+    assert_eq!("acd", elements);
+}
+
+#[test]
+fn matches_local_edits() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    let mut session = cfold.session(1);
+    session.extend("Hello world!".chars());
+    session.splice(LogIndex(6)..LogIndex(12), "there".chars());
+    session.insert_after(LogIndex(0), '>');
+    check_against_reference(&cfold).unwrap();
+}
+
+#[test]
+fn matches_ops_in_random_order() {
+    for seed in 0..20 {
+        let mut sim = Simulation::new(seed, 4, NetworkConfig::default());
+        sim.run(100);
+        sim.settle();
+        let mut ops: Vec<Op<usize, char>> =
+            sim.replicas()[0].iter_ops(..).map(Op::cloned).collect();
+
+        let mut rng = Rng::seed_from(seed);
+        for i in (1..ops.len()).rev() {
+            ops.swap(i, rng.gen_range(0..i + 1));
+        }
+        let cfold = check_ops_against_reference(ops).unwrap();
+        assert!(cfold.content_eq(&sim.replicas()[0]));
+    }
+}
+
+#[test]
+fn fails_on_rejected_and_missing_ops() {
+    let t = |idx, author| Timestamp(LogIndex(idx), author);
+    let root = Op::root(t(0, 0));
+    let a = Op::insert(t(1, 1), Some(t(0, 0)), 'a');
+
+    let forged = Op::insert(t(1, 1), Some(t(0, 0)), 'b');
+    let ops = vec![root.clone(), a.clone(), forged];
+    assert!(check_ops_against_reference(ops).is_err());
+
+    let orphan = Op::insert(t(2, 1), Some(t(5, 2)), 'c');
+    let ops = vec![root.clone(), a.clone(), orphan];
+    assert!(check_ops_against_reference(ops).is_err());
+
+    let ops = vec![a, root];
+    assert!(check_ops_against_reference(ops).is_ok());
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeSet;
+
+use chronofold::{AuthorId, AuthorRegistry, Chronofold, LogIndex, Op, RegistryError, RejectedOp};
+
+type Registry = AuthorRegistry<String>;
+
+/// Sends all ops of `source` unknown to `target` through the registries.
+fn sync(
+    source: &Chronofold<AuthorId, char>,
+    source_registry: &Registry,
+    target: &mut Chronofold<AuthorId, char>,
+    target_registry: &mut Registry,
+) {
+    let ops: Vec<Op<String, char>> = source
+        .iter_newer_ops(target.version())
+        .map(Op::cloned)
+        .map(|op| source_registry.resolve_op(op).unwrap())
+        .collect();
+    for op in ops {
+        target
+            .apply(target_registry.intern_op(op).unwrap())
+            .unwrap();
+    }
+}
+
+#[test]
+fn concurrent_edits_converge() {
+    let mut alice_registry = Registry::new();
+    let alice = alice_registry.intern("alice".to_string()).unwrap();
+    let mut alice_cfold = Chronofold::new(alice);
+    alice_cfold.session(alice).extend("Hello".chars());
+
+    let mut bob_registry = Registry::new();
+    let bob = bob_registry.intern("bob".to_string()).unwrap();
+    let mut bob_cfold = Chronofold::empty();
+    sync(
+        &alice_cfold,
+        &alice_registry,
+        &mut bob_cfold,
+        &mut bob_registry,
+    );
+
+    alice_cfold.session(alice).push_back('!');
+    bob_cfold.session(bob).push_back('?');
+    sync(
+        &alice_cfold,
+        &alice_registry,
+        &mut bob_cfold,
+        &mut bob_registry,
+    );
+    sync(
+        &bob_cfold,
+        &bob_registry,
+        &mut alice_cfold,
+        &mut alice_registry,
+    );
+
+    assert_eq!(format!("{alice_cfold}"), format!("{bob_cfold}"));
+    assert_eq!(alice_cfold.version(), bob_cfold.version());
+    assert_eq!(alice_registry, bob_registry);
+    assert_eq!(Some(&"bob".to_string()), alice_registry.resolve(bob));
+    assert_eq!(Some(LogIndex(6)), alice_cfold.version().get(&alice));
+}
+
+#[test]
+fn ids_are_stable() {
+    let mut registry = Registry::new();
+    let id = registry.intern("alice".to_string()).unwrap();
+    assert_eq!(AuthorId::of(&"alice"), id);
+    assert_eq!(Ok(id), registry.intern("alice".to_string()));
+    assert_eq!(Some(id), registry.id(&"alice".to_string()));
+    assert_eq!(None, registry.id(&"bob".to_string()));
+    assert_eq!(1, registry.len());
+}
+
+#[test]
+fn ids_are_distinct() {
+    // These collide with 32-bit FNV-1a hashes.
+    assert_ne!(AuthorId::of(&"costarring"), AuthorId::of(&"liquid"));
+    let ids: BTreeSet<AuthorId> = (0..100_000)
+        .map(|i| AuthorId::of(&format!("author-{i}")))
+        .collect();
+    assert_eq!(100_000, ids.len());
+}
+
+#[test]
+fn unknown_ids() {
+    let registry = Registry::new();
+    let mut cfold = Chronofold::<AuthorId, char>::new(AuthorId(1));
+    cfold.session(AuthorId(1)).push_back('!');
+    let op = cfold.iter_ops(..).map(Op::cloned).next().unwrap();
+    assert_eq!(
+        Err(RejectedOp {
+            error: RegistryError::UnknownId(AuthorId(1)),
+            op: op.clone()
+        }),
+        registry.resolve_op(op)
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialization() {
+    let mut registry = Registry::new();
+    registry.intern("bob".to_string()).unwrap();
+    registry.intern("alice".to_string()).unwrap();
+    let json = serde_json::to_string(&registry).unwrap();
+    assert_eq!(registry, serde_json::from_str(&json).unwrap());
+
+    let alice = registry.id(&"alice".to_string()).unwrap();
+    let mut cfold = Chronofold::<AuthorId, char>::new(alice);
+    cfold.session(alice).extend("Hi!".chars());
+    let json = serde_json::to_string(&cfold).unwrap();
+    assert_eq!(cfold, serde_json::from_str(&json).unwrap());
+}
@@ -0,0 +1,93 @@
+use chronofold::{Chronofold, ChronofoldError, LogIndex, Op, OpPayload, Relay, Timestamp};
+
+const KEY: u8 = 0x5a;
+
+fn encrypt(op: Op<u8, &char>) -> Op<u8, Vec<u8>> {
+    let payload = match op.payload {
+        OpPayload::Root => OpPayload::Root,
+        OpPayload::Insert(t, c) => {
+            OpPayload::Insert(t, c.to_string().bytes().map(|b| b ^ KEY).collect())
+        }
+        OpPayload::Delete(t) => OpPayload::Delete(t),
+    };
+    Op::new(op.id, payload)
+}
+
+fn decrypt(op: &Op<u8, Vec<u8>>) -> Op<u8, char> {
+    let payload = match &op.payload {
+        OpPayload::Root => OpPayload::Root,
+        OpPayload::Insert(t, bytes) => {
+            let bytes: Vec<u8> = bytes.iter().map(|b| b ^ KEY).collect();
+            let c = String::from_utf8(bytes).unwrap().chars().next().unwrap();
+            OpPayload::Insert(*t, c)
+        }
+        OpPayload::Delete(t) => OpPayload::Delete(*t),
+    };
+    Op::new(op.id, payload)
+}
+
+#[test]
+fn relays_encrypted_ops() {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    let mut relay = Relay::<u8>::new();
+    for op in cfold_a.iter_ops(..).map(encrypt) {
+        relay.apply(op).unwrap();
+    }
+    let mut cfold_b = cfold_a.clone();
+
+    let ops_a: Vec<Op<u8, Vec<u8>>> = {
+        let mut session = cfold_a.session(1);
+        session.extend("Hello world!".chars());
+        session.iter_ops().map(encrypt).collect()
+    };
+    let ops_b: Vec<Op<u8, Vec<u8>>> = {
+        let mut session = cfold_b.session(2);
+        session.extend("Hi!".chars());
+        session.iter_ops().map(encrypt).collect()
+    };
+    for op in ops_a.into_iter().chain(ops_b) {
+        relay.apply(op).unwrap();
+    }
+
+    for cfold in [&mut cfold_a, &mut cfold_b] {
+        let missing: Vec<Op<u8, char>> =
+            relay.iter_newer_ops(cfold.version()).map(decrypt).collect();
+        for op in missing {
+            cfold.apply(op).unwrap();
+        }
+        assert_eq!(relay.version(), cfold.version());
+    }
+    assert_eq!(format!("{}", cfold_a), format!("{}", cfold_b));
+}
+
+#[test]
+fn rejects_what_chronofolds_reject() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).extend("ab".chars());
+    let mut relay = Relay::<u8, char>::new();
+    for op in cfold.iter_ops(..).map(Op::cloned) {
+        relay.apply(op).unwrap();
+    }
+
+    let t = |idx, author| Timestamp(LogIndex(idx), author);
+    let ops = vec![
+        Op::insert(t(1, 1), Some(t(0, 0)), 'a'),
+        Op::insert(t(1, 1), Some(t(0, 0)), 'x'),
+        Op::insert(t(9, 2), Some(t(0, 0)), 'x'),
+        Op::insert(t(3, 2), Some(t(3, 1)), 'x'),
+        Op::insert(t(3, 2), None, 'x'),
+        Op::root(t(3, 2)),
+        Op::delete(t(3, 2), t(0, 0)),
+    ];
+    for op in ops {
+        let expected = cfold.clone().apply_checked(op.clone()).map(|_| ());
+        assert_eq!(expected, relay.apply(op));
+    }
+
+    // Deleting a delete is rejected as well.
+    relay.apply(Op::delete(t(3, 2), t(1, 1))).unwrap();
+    assert!(matches!(
+        relay.apply(Op::delete(t(4, 2), t(3, 2))),
+        Err(ChronofoldError::InvalidReferenceKind(_))
+    ));
+}
@@ -26,6 +26,65 @@ fn local_edits_only() {
     assert_json_max_len(&cfold, 767);
 }
 
+#[test]
+fn snapshot() {
+    let mut cfold = Chronofold::<usize, char>::default();
+    cfold.session(1).extend("Hello world!".chars());
+    cfold
+        .session(1)
+        .splice(LogIndex(6)..LogIndex(11), "cfold".chars());
+    let snapshot = cfold.snapshot();
+    let json = serde_json::to_string(&snapshot).unwrap();
+    assert!(json.len() < serde_json::to_string(&cfold).unwrap().len());
+    assert_eq!(snapshot, serde_json::from_str(&json).unwrap());
+}
+
+#[test]
+fn invalid_payloads() {
+    let valid = r#"{"log":[["Root",null],[{"Insert":"a"},3],[{"Insert":"b"},null],["Delete",null]],"root":0,"version":{"0":0,"1":3},"next_indices":{"map":{"1":2,"2":null,"3":-1}},"references":{"map":{"0":null,"3":-2}},"authors":{"map":{"0":0,"1":1}},"index_shifts":{"map":{"0":0}}}"#;
+    assert!(serde_json::from_str::<Chronofold<usize, char>>(valid).is_ok());
+
+    let cases = [
+        (r#""root":0"#, r#""root":1"#, "invalid root at log index 1"),
+        (
+            r#""2":null,"3":-1"#,
+            r#""2":-1,"3":-1"#,
+            "invalid next index at log index 2",
+        ),
+        (r#""3":-2"#, r#""3":5"#, "invalid reference at log index 3"),
+        (r#""3":-2"#, r#""3":-3"#, "invalid reference at log index 3"),
+        (
+            r#""0":null,"3""#,
+            r#""0":null,"2":null,"3""#,
+            "invalid reference at log index 2",
+        ),
+        (
+            r#"{"Insert":"a"},3"#,
+            r#"{"Insert":"a"},null"#,
+            "invalid deletion at log index 1",
+        ),
+        (
+            r#""1":1}}"#,
+            r#""1":1,"9":2}}"#,
+            "excess entry at log index 9",
+        ),
+        (r#""1":3}"#, r#""1":4}"#, "version doesn't match the log"),
+        (
+            r#""index_shifts":{"map":{"0":0}}"#,
+            r#""index_shifts":{"map":{"0":0,"2":1}}"#,
+            "invalid timestamp at log index 2",
+        ),
+    ];
+    for (from, to, message) in cases {
+        let json = valid.replace(from, to);
+        let err = serde_json::from_str::<Chronofold<usize, char>>(&json).unwrap_err();
+        assert!(
+            err.to_string().starts_with(message),
+            "expected {message:?}, got {err:?}"
+        );
+    }
+}
+
 fn assert_json_max_len(cfold: &Chronofold<usize, char>, max_len: usize) {
     let json = serde_json::to_string(&cfold).unwrap();
     assert!(
@@ -0,0 +1,57 @@
+use std::thread;
+
+use chronofold::{Chronofold, LogIndex, Op, SharedChronofold};
+
+#[test]
+fn concurrent_writers() {
+    let shared = SharedChronofold::new(Chronofold::<u8, char>::default());
+    let ops = shared.subscribe();
+
+    let threads: Vec<_> = (1..=4)
+        .map(|author| {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                for _ in 0..10 {
+                    shared.edit(author, |session| session.push_back('x'));
+                    assert!(!shared.read().is_empty());
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(40, shared.read().len());
+
+    // Subscribers receive ops in log order, so a replica can apply them
+    // right away.
+    let mut replica = Chronofold::<u8, char>::default();
+    for op in ops.try_iter() {
+        replica.apply(op).unwrap();
+    }
+    assert_eq!(*shared.read(), replica);
+}
+
+#[test]
+fn broadcasts_applied_ops() {
+    let shared = SharedChronofold::new(Chronofold::<u8, char>::default());
+    let mut remote = shared.read().clone();
+    let idx = remote.session(1).push_back('!');
+    let op: Op<u8, char> = remote.iter_ops(idx..).map(Op::cloned).next().unwrap();
+
+    let ops = shared.subscribe();
+    shared.apply(op.clone()).unwrap();
+    assert!(shared.apply(op.clone()).is_err());
+    assert_eq!(vec![op], ops.try_iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn dropped_subscribers() {
+    let shared = SharedChronofold::new(Chronofold::<u8, char>::default());
+    drop(shared.subscribe());
+    let ops = shared.subscribe();
+    let idx = shared.edit(1, |session| session.push_back('a'));
+    assert_eq!(LogIndex(1), idx);
+    assert_eq!(1, ops.try_iter().count());
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "testing")]
+
+use chronofold::testing::{Network, NetworkConfig, Rng, Simulation};
+use chronofold::{Chronofold, Op};
+
+#[test]
+fn replicas_converge() {
+    for seed in 0..20 {
+        let mut sim = Simulation::new(seed, 3, NetworkConfig::default());
+        sim.run(200);
+        sim.settle();
+        sim.check_convergence().unwrap();
+    }
+}
+
+#[test]
+fn runs_are_reproducible() {
+    let config = NetworkConfig {
+        max_delay: 20,
+        duplicate: 0.5,
+    };
+    let mut sim_a = Simulation::new(7, 4, config);
+    let mut sim_b = Simulation::new(7, 4, config);
+    sim_a.run(100);
+    sim_b.run(100);
+    assert_eq!(sim_a.replicas(), sim_b.replicas());
+}
+
+#[test]
+fn reports_divergence() {
+    let mut sim = Simulation::new(1, 2, NetworkConfig::default());
+    sim.run(20);
+    // Without settling, some ops are still in flight.
+    let err = sim.check_convergence().unwrap_err();
+    assert!(err.report.starts_with("seed 1: "));
+    assert!(err.report.contains("idx  | ref  | next | del  | change"));
+}
+
+#[test]
+fn network_reorders_and_duplicates() {
+    let mut rng = Rng::seed_from(3);
+    let mut network = Network::new(NetworkConfig {
+        max_delay: 10,
+        duplicate: 0.5,
+    });
+    for i in 0..100 {
+        network.send(&mut rng, 0, i);
+    }
+    let mut received = vec![];
+    while !network.is_idle() {
+        received.extend(network.tick(&mut rng).into_iter().map(|(_, m)| m));
+    }
+    assert!(received.len() > 100);
+    assert!(received.windows(2).any(|w| w[0] > w[1]));
+    received.sort_unstable();
+    received.dedup();
+    assert_eq!((0..100).collect::<Vec<_>>(), received);
+}
+
+#[test]
+fn runs_custom_editors() {
+    let push_or_pop = |rng: &mut Rng, author: u8, cfold: &mut Chronofold<u8, u64>| {
+        let last = cfold.iter().last().map(|(_, idx)| idx);
+        let mut session = cfold.session(author);
+        match last {
+            Some(idx) if rng.gen_bool(0.3) => session.remove(idx),
+            _ => {
+                session.push_back(rng.next_u64());
+            }
+        }
+        session.iter_ops().map(Op::cloned).collect()
+    };
+    let replicas = (1..=3).map(|author| (author, Chronofold::new(0)));
+    let mut sim = Simulation::with_replicas(5, replicas, NetworkConfig::default(), push_or_pop);
+    sim.run(200);
+    sim.settle();
+    sim.check_convergence().unwrap();
+    assert!(!sim.replicas()[0].is_empty());
+}
@@ -0,0 +1,73 @@
+use chronofold::{Chronofold, ChronofoldError, LogIndex, Op};
+
+#[test]
+fn contains_visible_elements_only() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).extend("Hello World!".chars());
+    cfold
+        .session(1)
+        .splice(LogIndex(7)..LogIndex(12), "Chronofold".chars());
+    let snapshot = cfold.snapshot();
+
+    assert_eq!(cfold.version(), snapshot.version());
+    assert_eq!(
+        "Hello Chronofold!",
+        snapshot.iter().map(|(_, c)| c).collect::<String>()
+    );
+    let restored = Chronofold::from_snapshot(snapshot);
+    assert_eq!("Hello Chronofold!", format!("{restored}"));
+    assert_eq!(cfold.version(), restored.version());
+}
+
+#[test]
+fn accepts_newer_ops() {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    cfold_a.session(1).extend("abcdef".chars());
+    cfold_a
+        .session(1)
+        .splice(LogIndex(2)..LogIndex(4), "x".chars());
+    let mut cfold_b = Chronofold::from_snapshot(cfold_a.snapshot());
+
+    let ops_a: Vec<Op<u8, char>> = {
+        let mut session = cfold_a.session(1);
+        session.splice(LogIndex(5)..LogIndex(6), "y".chars());
+        session.iter_ops().map(Op::cloned).collect()
+    };
+    let ops_b: Vec<Op<u8, char>> = {
+        let mut session = cfold_b.session(2);
+        session.extend("gh".chars());
+        session.push_front('_');
+        session.iter_ops().map(Op::cloned).collect()
+    };
+    for op in ops_a {
+        cfold_b.apply(op).unwrap();
+    }
+    for op in ops_b {
+        cfold_a.apply(op).unwrap();
+    }
+
+    assert_eq!("_axdyfgh", format!("{cfold_a}"));
+    assert_eq!(format!("{cfold_a}"), format!("{cfold_b}"));
+    assert_eq!(cfold_a.version(), cfold_b.version());
+}
+
+#[test]
+fn rejects_ops_covered_by_snapshot() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    cfold.session(1).extend("ab".chars());
+    cfold.session(1).remove(LogIndex(1));
+    let deletion: Op<u8, char> = cfold.iter_ops(LogIndex(3)..).next().unwrap().cloned();
+
+    let mut restored = Chronofold::from_snapshot(cfold.snapshot());
+    assert_eq!(
+        Err(ChronofoldError::ExistingTimestamp(deletion.clone())),
+        restored.apply(deletion)
+    );
+}
+
+#[test]
+fn empty() {
+    let cfold = Chronofold::<u8, char>::empty();
+    let restored = Chronofold::from_snapshot(cfold.snapshot());
+    assert_eq!(cfold, restored);
+}
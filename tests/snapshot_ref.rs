@@ -0,0 +1,89 @@
+use std::thread;
+
+use chronofold::{Chronofold, LogIndex, Op, SnapshotStorage, Version};
+
+type CFold = Chronofold<u8, char, SnapshotStorage<u8, char>>;
+
+#[test]
+fn unaffected_by_later_changes() {
+    let mut cfold = CFold::new_in(0, SnapshotStorage::new());
+    // Span multiple chunks.
+    cfold.session(1).extend("a".repeat(100).chars());
+    let snapshot = cfold.snapshot_ref();
+    let version = cfold.version().clone();
+    let ops: Vec<Op<u8, char>> = cfold.iter_ops(..).map(Op::cloned).collect();
+
+    cfold
+        .session(2)
+        .splice(LogIndex(10)..LogIndex(90), "b".chars());
+    cfold.session(2).push_front('c');
+
+    assert_eq!(100, snapshot.len());
+    assert_eq!(&version, snapshot.version());
+    assert_eq!("a".repeat(100), format!("{}", *snapshot));
+    assert_eq!(
+        ops,
+        snapshot.iter_ops(..).map(Op::cloned).collect::<Vec<_>>()
+    );
+    assert_eq!(22, cfold.len());
+    assert!(cfold.version() > &version);
+}
+
+#[test]
+fn same_behavior_as_memory_storage() {
+    let mut cfold = CFold::new_in(0, SnapshotStorage::new());
+    let mut reference = Chronofold::<u8, char>::new(0);
+    reference.session(1).extend("Hello world".chars());
+    reference
+        .session(1)
+        .splice(LogIndex(2)..LogIndex(5), "y".chars());
+    cfold.session(1).extend("Hello world".chars());
+    let _snapshot = cfold.snapshot_ref();
+    cfold
+        .session(1)
+        .splice(LogIndex(2)..LogIndex(5), "y".chars());
+
+    assert_eq!(reference.formatted_log(), cfold.formatted_log());
+    let ops: Vec<Op<u8, char>> = cfold
+        .iter_newer_ops(&Version::new())
+        .map(Op::cloned)
+        .collect();
+    let mut replica = Chronofold::<u8, char>::empty();
+    for op in ops {
+        replica.apply(op).unwrap();
+    }
+    assert_eq!(reference, replica);
+}
+
+#[test]
+fn read_on_another_thread() {
+    let mut cfold = CFold::new_in(0, SnapshotStorage::new());
+    cfold.session(1).extend("Hello".chars());
+    let snapshot = cfold.snapshot_ref();
+    let reader = thread::spawn(move || format!("{}", *snapshot));
+    cfold.session(1).extend(" world".chars());
+    assert_eq!("Hello", reader.join().unwrap());
+}
+
+#[test]
+fn large_logs() {
+    // Span multiple levels of the tree.
+    let text: String = (0..10_000)
+        .map(|i| (b'a' + (i % 26) as u8) as char)
+        .collect();
+    let mut cfold = CFold::new_in(0, SnapshotStorage::new());
+    let mut reference = Chronofold::<u8, char>::new(0);
+    reference.session(1).extend(text.chars());
+    cfold.session(1).extend(text.chars());
+    let snapshot = cfold.snapshot_ref();
+
+    for idx in [1, 64, 4_096, 4_097, 10_000] {
+        cfold.session(2).remove(LogIndex(idx));
+        reference.session(2).remove(LogIndex(idx));
+    }
+    cfold.session(2).push_back('!');
+    reference.session(2).push_back('!');
+
+    assert_eq!(text, format!("{}", *snapshot));
+    assert_eq!(reference.formatted_log(), cfold.formatted_log());
+}
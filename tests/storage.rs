@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use chronofold::{
+    Change, Chronofold, EarliestDeletion, LendingStorage, LogIndex, MemoryStorage, Op, Session,
+    Storage, Timestamp,
+};
+
+/// An entry of the log with all its secondary data.
+struct Entry {
+    change: (Change<char>, EarliestDeletion),
+    id: Timestamp<u8>,
+    reference: Option<LogIndex>,
+    next_index: Option<LogIndex>,
+}
+
+/// A storage keeping one record per log index, like a key-value store would.
+#[derive(Default)]
+struct KeyValueStorage {
+    entries: HashMap<LogIndex, Entry>,
+}
+
+impl Storage<u8, char> for KeyValueStorage {
+    type Entry<'a> = &'a (Change<char>, EarliestDeletion);
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn get(&self, index: LogIndex) -> Option<Self::Entry<'_>> {
+        self.get_ref(index)
+    }
+
+    fn push(
+        &mut self,
+        change: Change<char>,
+        id: Timestamp<u8>,
+        reference: Option<LogIndex>,
+        next_index: Option<LogIndex>,
+    ) {
+        let entry = Entry {
+            change: (change, None),
+            id,
+            reference,
+            next_index,
+        };
+        self.entries.insert(LogIndex(self.entries.len()), entry);
+    }
+
+    fn timestamp(&self, index: LogIndex) -> Option<Timestamp<u8>> {
+        self.entries.get(&index).map(|e| e.id)
+    }
+
+    fn reference(&self, index: LogIndex) -> Option<LogIndex> {
+        self.entries.get(&index)?.reference
+    }
+
+    fn next_index(&self, index: LogIndex) -> Option<LogIndex> {
+        self.entries.get(&index)?.next_index
+    }
+
+    fn set_next_index(&mut self, index: LogIndex, next_index: Option<LogIndex>) {
+        self.entries.get_mut(&index).unwrap().next_index = next_index;
+    }
+
+    fn set_earliest_deletion(&mut self, index: LogIndex, deletion: EarliestDeletion) {
+        self.entries.get_mut(&index).unwrap().change.1 = deletion;
+    }
+}
+
+impl LendingStorage<u8, char> for KeyValueStorage {
+    fn get_ref(&self, index: LogIndex) -> Option<&(Change<char>, EarliestDeletion)> {
+        self.entries.get(&index).map(|e| &e.change)
+    }
+}
+
+/// A storage returning copies of its changes, like one loading them from
+/// disk would.
+#[derive(Default)]
+struct CopyingStorage(MemoryStorage<u8, char>);
+
+impl Storage<u8, char> for CopyingStorage {
+    type Entry<'a> = Box<(Change<char>, EarliestDeletion)>;
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn get(&self, index: LogIndex) -> Option<Self::Entry<'_>> {
+        self.0.get(index).cloned().map(Box::new)
+    }
+
+    fn push(
+        &mut self,
+        change: Change<char>,
+        id: Timestamp<u8>,
+        reference: Option<LogIndex>,
+        next_index: Option<LogIndex>,
+    ) {
+        self.0.push(change, id, reference, next_index);
+    }
+
+    fn timestamp(&self, index: LogIndex) -> Option<Timestamp<u8>> {
+        self.0.timestamp(index)
+    }
+
+    fn reference(&self, index: LogIndex) -> Option<LogIndex> {
+        self.0.reference(index)
+    }
+
+    fn next_index(&self, index: LogIndex) -> Option<LogIndex> {
+        self.0.next_index(index)
+    }
+
+    fn set_next_index(&mut self, index: LogIndex, next_index: Option<LogIndex>) {
+        self.0.set_next_index(index, next_index);
+    }
+
+    fn set_earliest_deletion(&mut self, index: LogIndex, deletion: EarliestDeletion) {
+        self.0.set_earliest_deletion(index, deletion);
+    }
+}
+
+fn edit<S: Storage<u8, char>>(mut session: Session<'_, u8, char, S>) {
+    session.extend("Hello world".chars());
+    session.splice(LogIndex(1)..LogIndex(6), "Goodbye".chars());
+    session.insert_after(LogIndex(11), '!');
+}
+
+#[test]
+fn sessions() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    edit(cfold.session(1));
+    let mut kv_cfold = Chronofold::new_in(0, KeyValueStorage::default());
+    edit(kv_cfold.session(1));
+
+    assert_eq!("Goodbye world!", format!("{kv_cfold}"));
+    assert_eq!(cfold.digest(), kv_cfold.digest());
+    assert_eq!(cfold.formatted_log(), kv_cfold.formatted_log());
+    assert_eq!(
+        cfold.iter_ops(..).collect::<Vec<Op<u8, &char>>>(),
+        kv_cfold.iter_ops(..).collect::<Vec<Op<u8, &char>>>()
+    );
+}
+
+#[test]
+fn extend_after_edits() {
+    let mut kv_cfold = Chronofold::new_in(0, KeyValueStorage::default());
+    edit(kv_cfold.session(1));
+    kv_cfold
+        .session(1)
+        .splice(LogIndex(22)..LogIndex(23), "a".chars());
+    kv_cfold.session(1).extend("!!".chars());
+    assert_eq!("Goodbae world!!!", format!("{kv_cfold}"));
+}
+
+#[test]
+fn apply_ops() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    edit(cfold.session(1));
+    let mut kv_cfold = Chronofold::empty_in(KeyValueStorage::default());
+    for op in cfold.iter_ops(..).map(Op::cloned) {
+        kv_cfold.apply(op).unwrap();
+    }
+    assert!(cfold.content_eq(&kv_cfold));
+    assert_eq!(cfold.version(), kv_cfold.version());
+
+    // Concurrent edits converge.
+    let ops: Vec<Op<u8, char>> = {
+        let mut session = kv_cfold.session(2);
+        session.push_front('>');
+        session.iter_ops().map(Op::cloned).collect()
+    };
+    cfold.session(3).push_back('?');
+    for op in ops {
+        cfold.apply(op).unwrap();
+    }
+    for op in cfold
+        .iter_newer_ops(kv_cfold.version())
+        .map(Op::cloned)
+        .collect::<Vec<_>>()
+    {
+        kv_cfold.apply(op).unwrap();
+    }
+    assert_eq!(">Goodbye world!?", format!("{cfold}"));
+    assert_eq!(format!("{cfold}"), format!("{kv_cfold}"));
+}
+
+#[test]
+fn copying_storage() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    edit(cfold.session(1));
+    let mut copying_cfold = Chronofold::new_in(0, CopyingStorage::default());
+    edit(copying_cfold.session(1));
+
+    assert_eq!("Goodbye world!", format!("{copying_cfold}"));
+    assert_eq!(14, copying_cfold.len());
+    assert_eq!(cfold.digest(), copying_cfold.digest());
+    assert_eq!(cfold.formatted_log(), copying_cfold.formatted_log());
+    assert_eq!(Ok(()), copying_cfold.check_invariants());
+
+    let mut replica = Chronofold::empty_in(CopyingStorage::default());
+    for op in cfold.iter_ops(..).map(Op::cloned) {
+        replica.apply(op).unwrap();
+    }
+    replica.session(2).remove(LogIndex(7));
+    assert_eq!("Goodbye orld!", format!("{replica}"));
+}
+
+#[test]
+fn sync_from_copying_storage() {
+    let mut copying_cfold = Chronofold::new_in(0, CopyingStorage::default());
+    edit(copying_cfold.session(1));
+    let mut cfold = Chronofold::<u8, char>::empty();
+    let version = cfold.version().clone();
+    for op in copying_cfold.iter_newer_cloned_ops(&version) {
+        cfold.apply(op).unwrap();
+    }
+    assert_eq!("Goodbye world!", format!("{cfold}"));
+
+    copying_cfold.session(2).remove(LogIndex(7));
+    let ops: Vec<Op<u8, char>> = copying_cfold
+        .iter_newer_cloned_ops(cfold.version())
+        .collect();
+    assert_eq!(1, ops.len());
+    for op in ops {
+        cfold.apply(op).unwrap();
+    }
+    assert_eq!("Goodbye orld!", format!("{cfold}"));
+    assert_eq!(copying_cfold.digest(), cfold.digest());
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "sync")]
+
+use chronofold::{Chronofold, ChronofoldError, LogIndex, SyncError, SyncSession};
+
+type Network = Box<dyn Fn(Vec<Vec<u8>>) -> Vec<Vec<u8>>>;
+
+/// Runs a sync session between both chronofolds until no messages are left.
+///
+/// `network` decides which messages of a round get delivered in what order.
+fn sync(
+    cfold_a: &mut Chronofold<u8, char>,
+    cfold_b: &mut Chronofold<u8, char>,
+    batch_size: usize,
+    network: Network,
+) -> (SyncSession<u8, char>, SyncSession<u8, char>) {
+    let mut session_a = SyncSession::new().with_batch_size(batch_size);
+    let mut session_b = SyncSession::new().with_batch_size(batch_size);
+    let mut to_b = session_a.start(cfold_a);
+    let mut to_a = session_b.start(cfold_b);
+    while !to_a.is_empty() || !to_b.is_empty() {
+        let mut next_to_a = vec![];
+        let mut next_to_b = vec![];
+        for message in network(std::mem::take(&mut to_b)) {
+            next_to_a.extend(session_b.receive(cfold_b, &message).unwrap());
+        }
+        for message in network(std::mem::take(&mut to_a)) {
+            next_to_b.extend(session_a.receive(cfold_a, &message).unwrap());
+        }
+        to_a = next_to_a;
+        to_b = next_to_b;
+    }
+    (session_a, session_b)
+}
+
+fn diverged() -> (Chronofold<u8, char>, Chronofold<u8, char>) {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    cfold_a.session(1).extend("Hello world!".chars());
+    let mut cfold_b = cfold_a.clone();
+    cfold_a
+        .session(1)
+        .splice(LogIndex(7)..LogIndex(12), "there".chars());
+    cfold_b.session(2).extend(" Bye!".chars());
+    (cfold_a, cfold_b)
+}
+
+#[test]
+fn syncs_both_replicas() {
+    let (mut cfold_a, mut cfold_b) = diverged();
+    let (session_a, session_b) = sync(&mut cfold_a, &mut cfold_b, 1000, Box::new(|m| m));
+    assert!(session_a.is_complete());
+    assert!(session_b.is_complete());
+    assert_eq!("Hello there! Bye!", format!("{}", cfold_a));
+    assert_eq!(cfold_a.digest(), cfold_b.digest());
+    assert!(cfold_a.content_eq(&cfold_b));
+}
+
+#[test]
+fn handles_reordered_and_duplicated_messages() {
+    let (mut cfold_a, mut cfold_b) = diverged();
+    let network = |messages: Vec<Vec<u8>>| {
+        let mut reordered: Vec<Vec<u8>> = messages.into_iter().rev().collect();
+        reordered.extend(reordered.clone());
+        reordered
+    };
+    let (session_a, session_b) = sync(&mut cfold_a, &mut cfold_b, 1, Box::new(network));
+    assert!(session_a.is_complete());
+    assert!(session_b.is_complete());
+    assert_eq!("Hello there! Bye!", format!("{}", cfold_a));
+    assert_eq!(cfold_a.digest(), cfold_b.digest());
+    assert!(cfold_a.content_eq(&cfold_b));
+}
+
+#[test]
+fn rejects_malformed_messages() {
+    let mut cfold = Chronofold::<u8, char>::default();
+    let mut session = SyncSession::new();
+    assert!(matches!(
+        session.receive(&mut cfold, b"not a message"),
+        Err(SyncError::Malformed(_))
+    ));
+}
+
+#[test]
+fn limits_pending_ops() {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    cfold_a.session(1).extend("Hello".chars());
+    let mut cfold_b = Chronofold::<u8, char>::default();
+    let mut session_a = SyncSession::new().with_batch_size(1);
+    let mut session_b = SyncSession::new().with_max_pending(2);
+    let hello = session_b.start(&cfold_b).remove(0);
+    let messages = session_a.receive(&mut cfold_a, &hello).unwrap();
+
+    // Without the first op, none of the others can be applied.
+    let mut results = messages[1..5]
+        .iter()
+        .map(|message| session_b.receive(&mut cfold_b, message));
+    assert!(results.next().unwrap().is_ok());
+    assert!(results.next().unwrap().is_ok());
+    assert!(matches!(
+        results.next().unwrap(),
+        Err(SyncError::TooManyPending(3))
+    ));
+    assert!(results.next().unwrap().is_ok());
+    assert_eq!("", format!("{cfold_b}"));
+}
+
+#[test]
+fn keeps_pending_ops_after_rejected_ops() {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    cfold_a.session(1).extend("Hel".chars());
+    let mut cfold_b = Chronofold::<u8, char>::default();
+    // Reuses the timestamp of 'H', followed by a valid op of another author.
+    let mut cfold_c = Chronofold::<u8, char>::default();
+    cfold_c.session(1).push_back('X');
+    cfold_c.session(3).push_back('Z');
+
+    let mut session_a = SyncSession::new().with_batch_size(1);
+    let mut session_b = SyncSession::new();
+    let mut session_c = SyncSession::new();
+    let hello = session_b.start(&cfold_b).remove(0);
+    let from_a = session_a.receive(&mut cfold_a, &hello).unwrap();
+    let from_c = session_c.receive(&mut cfold_c, &hello).unwrap();
+
+    session_b.receive(&mut cfold_b, &from_a[0]).unwrap();
+    session_b.receive(&mut cfold_b, &from_a[2]).unwrap();
+    assert!(matches!(
+        session_b.receive(&mut cfold_b, &from_c[0]),
+        Err(SyncError::Apply(ChronofoldError::Equivocation { .. }))
+    ));
+    session_b.receive(&mut cfold_b, &from_a[1]).unwrap();
+    assert_eq!(4, cfold_b.len());
+    assert!(format!("{cfold_b}").contains('Z'));
+}
@@ -0,0 +1,99 @@
+use chronofold::{Chronofold, DigestNode, LogIndex, Op};
+
+#[test]
+fn finds_gaps() {
+    let mut cfold_a = Chronofold::<u8, char>::default();
+    cfold_a.session(1).extend("Hello world!".chars());
+    let mut cfold_b = cfold_a.clone();
+
+    // Alice deletes the 'o', but that op gets lost on its way to Bob.
+    let lost: Op<u8, char> = {
+        let mut session = cfold_a.session(1);
+        session.remove(LogIndex(5));
+        let op = session.iter_ops().map(Op::cloned).next().unwrap();
+        op
+    };
+    let ops_b: Vec<Op<u8, char>> = {
+        let mut session = cfold_b.session(2);
+        session.extend((0..100).map(|_| '.'));
+        session.iter_ops().map(Op::cloned).collect()
+    };
+    for op in ops_b {
+        cfold_a.apply(op).unwrap();
+    }
+    let ops_a: Vec<Op<u8, char>> = {
+        let mut session = cfold_a.session(1);
+        session.extend(" Bye!".chars());
+        session.iter_ops().map(Op::cloned).collect()
+    };
+    // Concurrently, Bob continues editing.
+    let ops_b: Vec<Op<u8, char>> = {
+        let mut session = cfold_b.session(2);
+        session.extend("?!".chars());
+        session.iter_ops().map(Op::cloned).collect()
+    };
+    for op in ops_b {
+        cfold_a.apply(op).unwrap();
+    }
+    for op in ops_a.into_iter().filter(|op| *op != lost) {
+        cfold_b.apply(op).unwrap();
+    }
+    assert_eq!(cfold_a.version(), cfold_b.version());
+    assert_ne!(cfold_a.digest(), cfold_b.digest());
+
+    let mut digest = cfold_a.sync_digest();
+    let mut round_trips = 0;
+    let mut differing_leaves = vec![];
+    while !digest.is_empty() {
+        let differing = cfold_b.compare_sync_digest(&digest);
+        differing_leaves.extend(differing.iter().filter(|node| node.is_leaf()).copied());
+        digest = cfold_a.sync_digest_of(differing.iter().flat_map(|node| node.children()));
+        round_trips += 1;
+    }
+    assert_eq!(4, round_trips);
+    assert_eq!(
+        vec![DigestNode {
+            author: 1,
+            level: 0,
+            index: 0
+        }],
+        differing_leaves
+    );
+
+    let repair: Vec<Op<u8, char>> = cfold_a
+        .iter_digest_node_ops(&differing_leaves[0])
+        .map(Op::cloned)
+        .collect();
+    assert!(repair.contains(&lost));
+    for op in repair {
+        cfold_b.apply_idempotent(op).unwrap();
+    }
+    assert_eq!(cfold_a.digest(), cfold_b.digest());
+    assert!(cfold_b
+        .compare_sync_digest(&cfold_a.sync_digest())
+        .is_empty());
+}
+
+#[test]
+fn node_ranges() {
+    let node = DigestNode {
+        author: 1,
+        level: 2,
+        index: 3,
+    };
+    assert_eq!(LogIndex(192)..LogIndex(256), node.range());
+    assert_eq!(
+        vec![LogIndex(192)..LogIndex(224), LogIndex(224)..LogIndex(256)],
+        node.children()
+            .iter()
+            .map(DigestNode::range)
+            .collect::<Vec<_>>()
+    );
+
+    let huge = DigestNode {
+        author: 1,
+        level: u32::MAX,
+        index: usize::MAX,
+    };
+    assert_eq!(LogIndex(usize::MAX)..LogIndex(usize::MAX), huge.range());
+}